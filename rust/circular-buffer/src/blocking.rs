@@ -0,0 +1,131 @@
+//! A bounded queue which any number of producer and consumer threads can share,
+//! with reads and writes which wait for room or for elements.
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::{CircularBuffer, Error};
+
+struct State<T> {
+    buffer: CircularBuffer<T>,
+    closed: bool,
+}
+
+/// A `CircularBuffer` behind a lock, to be shared between threads in an `Arc`.
+///
+/// Once closed, writes fail with `Error::Closed`, and reads return the elements
+/// left in the buffer before failing with `Error::Closed` too.
+pub struct BlockingCircularBuffer<T> {
+    state: Mutex<State<T>>,
+    // Signalled when an element is read, or the buffer is closed
+    not_full: Condvar,
+    // Signalled when an element is written, or the buffer is closed
+    not_empty: Condvar,
+}
+
+impl<T> BlockingCircularBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        BlockingCircularBuffer {
+            state: Mutex::new(State {
+                buffer: CircularBuffer::new(capacity),
+                closed: false,
+            }),
+            not_full: Condvar::new(),
+            not_empty: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        // The buffer is left consistent by every operation, so a thread which
+        // panicked while holding the lock does not make it unusable
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Write without waiting, failing with `Error::FullBuffer` if there is no room.
+    pub fn write(&self, element: T) -> Result<(), Error> {
+        let state = self.lock();
+        self.write_locked(state, element)
+    }
+
+    /// Read without waiting, failing with `Error::EmptyBuffer` if there is nothing to read.
+    pub fn read(&self) -> Result<T, Error> {
+        let state = self.lock();
+        self.read_locked(state)
+    }
+
+    /// Wait until there is room for the element, then write it.
+    pub fn write_blocking(&self, element: T) -> Result<(), Error> {
+        let state = self
+            .not_full
+            .wait_while(self.lock(), |state| state.buffer.is_full() && !state.closed)
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.write_locked(state, element)
+    }
+
+    /// Wait until there is an element, then read it.
+    pub fn read_blocking(&self) -> Result<T, Error> {
+        let state = self
+            .not_empty
+            .wait_while(self.lock(), |state| state.buffer.is_empty && !state.closed)
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.read_locked(state)
+    }
+
+    /// Wait up to `timeout` for room for the element, failing with
+    /// `Error::FullBuffer` if there is still no room.
+    pub fn write_timeout(&self, element: T, timeout: Duration) -> Result<(), Error> {
+        let (state, _timed_out) = self
+            .not_full
+            .wait_timeout_while(self.lock(), timeout, |state| {
+                state.buffer.is_full() && !state.closed
+            })
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.write_locked(state, element)
+    }
+
+    /// Wait up to `timeout` for an element, failing with `Error::EmptyBuffer`
+    /// if there is still nothing to read.
+    pub fn read_timeout(&self, timeout: Duration) -> Result<T, Error> {
+        let (state, _timed_out) = self
+            .not_empty
+            .wait_timeout_while(self.lock(), timeout, |state| {
+                state.buffer.is_empty && !state.closed
+            })
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.read_locked(state)
+    }
+
+    /// Close the buffer and wake every waiting thread.
+    pub fn close(&self) {
+        self.lock().closed = true;
+        self.not_full.notify_all();
+        self.not_empty.notify_all();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    fn write_locked(&self, mut state: MutexGuard<'_, State<T>>, element: T) -> Result<(), Error> {
+        if state.closed {
+            return Err(Error::Closed);
+        }
+        state.buffer.write(element)?;
+        drop(state);
+        self.not_empty.notify_one();
+        Ok(())
+    }
+
+    fn read_locked(&self, mut state: MutexGuard<'_, State<T>>) -> Result<T, Error> {
+        match state.buffer.read() {
+            Ok(element) => {
+                drop(state);
+                self.not_full.notify_one();
+                Ok(element)
+            }
+            Err(_) if state.closed => Err(Error::Closed),
+            Err(err) => Err(err),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! A circular buffer with a capacity fixed at compile time, which keeps its
+//! elements inline instead of on the heap.
+use core::mem::MaybeUninit;
+
+use crate::{assume_init_slice, Error};
+
+/// The same buffer as `CircularBuffer`, holding up to `N` elements without
+/// allocating, so that it can be used without `std` or `alloc`.
+pub struct StaticCircularBuffer<T, const N: usize> {
+    // The same layout as for 'CircularBuffer': the slots from 'oldest' up to,
+    // but not including, 'next' hold the initialized elements, oldest first.
+    buffer: [MaybeUninit<T>; N],
+    oldest: usize,
+    next: usize,
+    is_empty: bool,
+}
+
+impl<T, const N: usize> StaticCircularBuffer<T, N> {
+    const UNINIT: MaybeUninit<T> = MaybeUninit::uninit();
+
+    pub const fn new() -> Self {
+        StaticCircularBuffer {
+            buffer: [Self::UNINIT; N],
+            oldest: 0,
+            next: 0,
+            is_empty: true,
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        if self.is_empty {
+            0
+        } else if self.next > self.oldest {
+            self.next - self.oldest
+        } else {
+            N - self.oldest + self.next
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.is_empty
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.oldest == self.next && !self.is_empty
+    }
+
+    pub fn write(&mut self, element: T) -> Result<(), Error> {
+        if N == 0 {
+            return Err(Error::ZeroCapacity);
+        }
+        if self.is_full() {
+            return Err(Error::FullBuffer);
+        }
+        self.is_empty = false;
+        self.buffer[self.next].write(element);
+        self.next = (self.next + 1) % N;
+        Ok(())
+    }
+
+    pub fn read(&mut self) -> Result<T, Error> {
+        if self.is_empty {
+            return Err(Error::EmptyBuffer);
+        }
+        // SAFETY: The buffer is not empty, so the slot at 'oldest' is initialized.
+        // Moving 'oldest' past it below marks it as uninitialized again.
+        let element = unsafe { self.buffer[self.oldest].assume_init_read() };
+        self.oldest = (self.oldest + 1) % N;
+        if self.oldest == self.next {
+            self.is_empty = true;
+        }
+        Ok(element)
+    }
+
+    pub fn clear(&mut self) {
+        // Drop the remaining elements one by one, oldest first
+        while self.read().is_ok() {}
+        self.oldest = 0;
+        self.next = 0;
+    }
+
+    /// Write the element, dropping the oldest element to make room if the
+    /// buffer is full.
+    pub fn overwrite(&mut self, element: T) {
+        if self.is_full() {
+            let _ = self.read();
+        }
+        let _ = self.write(element);
+    }
+
+    /// The oldest element, which is the next to be read.
+    pub fn peek(&self) -> Result<&T, Error> {
+        self.iter().next().ok_or(Error::EmptyBuffer)
+    }
+
+    /// The newest element, which was the last to be written.
+    pub fn peek_newest(&self) -> Result<&T, Error> {
+        self.iter().next_back().ok_or(Error::EmptyBuffer)
+    }
+
+    /// The elements from oldest to newest.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> {
+        let (first, second) = self.as_slices();
+        first.iter().chain(second)
+    }
+
+    /// The elements as two contiguous slices, as for `CircularBuffer::as_slices`.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (first, second) = if self.is_empty {
+            (0..0, 0..0)
+        } else if self.next > self.oldest {
+            (self.oldest..self.next, 0..0)
+        } else {
+            (self.oldest..N, 0..self.next)
+        };
+        // SAFETY: The ranges cover exactly the initialized slots.
+        unsafe {
+            (
+                assume_init_slice(&self.buffer[first]),
+                assume_init_slice(&self.buffer[second]),
+            )
+        }
+    }
+}
+
+impl<T, const N: usize> Default for StaticCircularBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for StaticCircularBuffer<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
@@ -1,47 +1,287 @@
-use std::mem;
+//! Without the default `std` feature, only `StaticCircularBuffer` is available,
+//! and the crate needs neither `std` nor `alloc`.
+#![cfg_attr(not(feature = "std"), no_std)]
 
+use core::mem::MaybeUninit;
+#[cfg(feature = "std")]
+use core::ops::{Index, IndexMut, Range};
+use core::slice;
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "std")]
+mod blocking;
+mod fixed;
+#[cfg(feature = "std")]
+mod spsc;
+#[cfg(feature = "std")]
+mod window;
+
+#[cfg(feature = "std")]
+pub use blocking::BlockingCircularBuffer;
+pub use fixed::StaticCircularBuffer;
+#[cfg(feature = "std")]
+pub use spsc::{spsc, Consumer, Producer};
+#[cfg(feature = "std")]
+pub use window::{TimeWindow, WindowError};
+
+#[cfg(feature = "std")]
 pub struct CircularBuffer<T> {
     // The oldest element, if any, is at index 'oldest'. The age of the elements
     // increase to the right of 'oldest' (and wraps around if 'oldest' > 0).
-    buffer: Vec<T>,
+    // Only the slots from 'oldest' up to, but not including, 'next' hold
+    // initialized elements; the rest are uninitialized.
+    buffer: Box<[MaybeUninit<T>]>,
     oldest: usize,
     next: usize,
     is_empty: bool,
+    // Receives the elements pushed out by 'overwrite', if set
+    on_evict: Option<EvictionHook<T>>,
+    // The number of elements pushed out by 'overwrite' and 'overwrite_evict'
+    evicted: u64,
+    // Whether to double the capacity instead of failing when full
+    growable: bool,
 }
 
+/// The hook set by `set_eviction_hook`.
+#[cfg(feature = "std")]
+struct EvictionHook<T>(Box<dyn FnMut(T) + Send>);
+
+// SAFETY: The hook is only ever called through '&mut CircularBuffer', and a
+// shared reference gives no access to it, so sharing the buffer between threads
+// cannot lead to the hook being called from two threads at once.
+#[cfg(feature = "std")]
+unsafe impl<T> Sync for EvictionHook<T> {}
+
 #[derive(Debug, PartialEq)]
 pub enum Error {
     EmptyBuffer,
     FullBuffer,
+    /// The buffer was closed, and nothing more can be read from or written to it.
+    Closed,
+    /// The buffer has no room for any elements at all.
+    ZeroCapacity,
+    /// Resizing would remove elements, which `ShrinkPolicy::Reject` forbids.
+    WouldTruncate,
+}
+
+/// What `resize` does when the buffer holds more elements than the new capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShrinkPolicy {
+    /// Drop the oldest elements which no longer fit.
+    DropOldest,
+    /// Evict the oldest elements which no longer fit, as `overwrite` does: they
+    /// go to the eviction hook, if there is one, and are counted as evicted.
+    EvictOldest,
+    /// Fail with `Error::WouldTruncate`, leaving the buffer as it was.
+    Reject,
 }
 
-impl<T> CircularBuffer<T>
-where
-    T: Default,
-{
+#[cfg(feature = "std")]
+impl<T> CircularBuffer<T> {
     pub fn new(capacity: usize) -> Self {
-        let mut zeros = Vec::with_capacity(capacity);
-        for _ in 0..capacity {
-            zeros.push(Default::default());
-        }
         CircularBuffer {
-            buffer: zeros,
+            buffer: (0..capacity).map(|_| MaybeUninit::uninit()).collect(),
             oldest: 0,
             next: 0,
             is_empty: true,
+            on_evict: None,
+            evicted: 0,
+            growable: false,
+        }
+    }
+
+    /// Create a buffer which doubles its capacity when it is full, instead of
+    /// failing writes with `Error::FullBuffer`.
+    pub fn growable(capacity: usize) -> Self {
+        let mut buffer = Self::new(capacity);
+        buffer.growable = true;
+        buffer
+    }
+
+    /// Turn doubling the capacity when full on or off.
+    pub fn set_growable(&mut self, growable: bool) {
+        self.growable = growable;
+    }
+
+    pub fn is_growable(&self) -> bool {
+        self.growable
+    }
+
+    /// Change the capacity, keeping the elements in order. If there are more
+    /// elements than the new capacity, the oldest of them are removed
+    /// according to `policy`.
+    pub fn resize(&mut self, capacity: usize, policy: ShrinkPolicy) -> Result<(), Error> {
+        if capacity == 0 {
+            return Err(Error::ZeroCapacity);
+        }
+        let excess = self.len().saturating_sub(capacity);
+        if excess > 0 {
+            match policy {
+                ShrinkPolicy::Reject => return Err(Error::WouldTruncate),
+                ShrinkPolicy::DropOldest => {
+                    for _ in 0..excess {
+                        let _ = self.read();
+                    }
+                }
+                ShrinkPolicy::EvictOldest => {
+                    for _ in 0..excess {
+                        if let Ok(evicted) = self.read() {
+                            self.evicted += 1;
+                            if let Some(EvictionHook(on_evict)) = &mut self.on_evict {
+                                on_evict(evicted);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        self.reallocate(capacity);
+        Ok(())
+    }
+
+    /// Move the elements, oldest first, to the start of new storage with room
+    /// for `capacity` elements, which must be at least as many as there are.
+    fn reallocate(&mut self, capacity: usize) {
+        let mut buffer: Box<[MaybeUninit<T>]> =
+            (0..capacity).map(|_| MaybeUninit::uninit()).collect();
+        let mut len = 0;
+        while let Ok(element) = self.read() {
+            buffer[len].write(element);
+            len += 1;
+        }
+        // The old storage only holds uninitialized slots now
+        self.buffer = buffer;
+        self.oldest = 0;
+        self.next = len % capacity;
+        self.is_empty = len == 0;
+    }
+
+    /// Make room for at least `additional` more elements by doubling the capacity
+    /// as many times as needed.
+    fn grow(&mut self, additional: usize) {
+        let needed = self.len() + additional;
+        let mut capacity = self.capacity().max(1);
+        while capacity < needed {
+            capacity *= 2;
+        }
+        if capacity != self.capacity() {
+            self.reallocate(capacity);
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn len(&self) -> usize {
+        if self.is_empty {
+            0
+        } else if self.next > self.oldest {
+            self.next - self.oldest
+        } else {
+            self.buffer.len() - self.oldest + self.next
         }
     }
 
-    fn is_full(&self) -> bool {
+    pub fn is_empty(&self) -> bool {
+        self.is_empty
+    }
+
+    pub fn is_full(&self) -> bool {
         self.oldest == self.next && !self.is_empty
     }
 
+    /// The element at position `index`, counting from the oldest element.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        let (first, second) = self.as_slices();
+        first.get(index).or_else(|| second.get(index - first.len()))
+    }
+
+    /// The element at position `index`, counting from the oldest element.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        let (first, second) = self.as_mut_slices();
+        if index < first.len() {
+            first.get_mut(index)
+        } else {
+            second.get_mut(index - first.len())
+        }
+    }
+
+    /// The oldest element, which is the next to be read.
+    pub fn peek(&self) -> Result<&T, Error> {
+        self.get(0).ok_or(Error::EmptyBuffer)
+    }
+
+    /// The newest element, which was the last to be written.
+    pub fn peek_newest(&self) -> Result<&T, Error> {
+        let len = self.len();
+        len.checked_sub(1)
+            .and_then(|index| self.get(index))
+            .ok_or(Error::EmptyBuffer)
+    }
+
+    /// The elements from oldest to newest.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> {
+        let (first, second) = self.as_slices();
+        first.iter().chain(second)
+    }
+
+    /// The elements from oldest to newest.
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut T> {
+        let (first, second) = self.as_mut_slices();
+        first.iter_mut().chain(second)
+    }
+
+    /// The elements as two contiguous slices, which together hold every element
+    /// from oldest to newest. The second slice is empty unless the elements wrap
+    /// around the end of the storage.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (first, second) = self.initialized_ranges();
+        // SAFETY: The ranges cover exactly the initialized slots.
+        unsafe {
+            (
+                assume_init_slice(&self.buffer[first]),
+                assume_init_slice(&self.buffer[second]),
+            )
+        }
+    }
+
+    /// The elements as two contiguous mutable slices, as for `as_slices`.
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (first, second) = self.initialized_ranges();
+        // The second range, if any, ends before the first one starts
+        let (head, tail) = self.buffer.split_at_mut(first.start);
+        // SAFETY: The ranges cover exactly the initialized slots.
+        unsafe {
+            (
+                assume_init_slice_mut(&mut tail[..first.len()]),
+                assume_init_slice_mut(&mut head[second]),
+            )
+        }
+    }
+
+    /// The ranges of initialized slots in the storage, from oldest to newest.
+    fn initialized_ranges(&self) -> (Range<usize>, Range<usize>) {
+        if self.is_empty {
+            (0..0, 0..0)
+        } else if self.next > self.oldest {
+            (self.oldest..self.next, 0..0)
+        } else {
+            (self.oldest..self.buffer.len(), 0..self.next)
+        }
+    }
+
     pub fn write(&mut self, element: T) -> Result<(), Error> {
-        if self.is_full() {
+        if self.growable {
+            self.grow(1);
+        } else if self.capacity() == 0 {
+            return Err(Error::ZeroCapacity);
+        } else if self.is_full() {
             return Err(Error::FullBuffer);
         }
         self.is_empty = false;
-        self.buffer[self.next] = element;
+        self.buffer[self.next].write(element);
         self.next = (self.next + 1) % self.buffer.len();
         Ok(())
     }
@@ -50,30 +290,225 @@ where
         if self.is_empty {
             return Err(Error::EmptyBuffer);
         }
-        let mut empty = Default::default();
-        mem::swap(&mut empty, self.buffer.get_mut(self.oldest).unwrap());
+        // SAFETY: The buffer is not empty, so the slot at 'oldest' is initialized.
+        // Moving 'oldest' past it below marks it as uninitialized again, so the
+        // element is never read twice.
+        let element = unsafe { self.buffer[self.oldest].assume_init_read() };
         self.oldest = (self.oldest + 1) % self.buffer.len();
         if self.oldest == self.next {
             self.is_empty = true;
         }
-        Ok(empty)
+        Ok(element)
     }
 
-    pub fn clear(&mut self) {
-        for i in 0..self.buffer.capacity() {
-            self.buffer[i] = Default::default();
+    /// Read the newest element instead of the oldest one.
+    pub fn read_newest(&mut self) -> Result<T, Error> {
+        if self.is_empty {
+            return Err(Error::EmptyBuffer);
+        }
+        // The newest element is just before 'next', wrapping around to the end
+        self.next = (self.next + self.buffer.len() - 1) % self.buffer.len();
+        // SAFETY: The buffer is not empty, so the slot before 'next' is initialized,
+        // and moving 'next' back onto it above marks it as uninitialized again.
+        let element = unsafe { self.buffer[self.next].assume_init_read() };
+        if self.oldest == self.next {
+            self.is_empty = true;
         }
+        Ok(element)
+    }
+
+    pub fn clear(&mut self) {
+        // Drop the remaining elements one by one, oldest first
+        while self.read().is_ok() {}
         self.oldest = 0;
         self.next = 0;
-        self.is_empty = true
     }
 
+    /// Write the element, making room by evicting the oldest element if the
+    /// buffer is full. The evicted element goes to the eviction hook if there
+    /// is one, and is dropped otherwise.
     pub fn overwrite(&mut self, element: T) {
+        if let Some(evicted) = self.overwrite_evict(element) {
+            if let Some(EvictionHook(on_evict)) = &mut self.on_evict {
+                on_evict(evicted);
+            }
+        }
+    }
+
+    /// Write the element, making room by evicting the oldest element if the
+    /// buffer is full. The evicted element is returned instead of being passed
+    /// to the eviction hook.
+    pub fn overwrite_evict(&mut self, element: T) -> Option<T> {
+        if self.capacity() == 0 && !self.growable {
+            // There is no room at all, so the new element is evicted right away
+            self.evicted += 1;
+            return Some(element);
+        }
+        let evicted = if self.is_full() && !self.growable {
+            self.evicted += 1;
+            self.read().ok()
+        } else {
+            None
+        };
+        let _ = self.write(element);
+        evicted
+    }
+
+    /// Set a hook to receive each element that `overwrite` evicts, such as
+    /// for spilling them to disk. Replaces any previous hook.
+    pub fn set_eviction_hook(&mut self, on_evict: impl FnMut(T) + Send + 'static) {
+        self.on_evict = Some(EvictionHook(Box::new(on_evict)));
+    }
+
+    /// Remove the eviction hook, so that evicted elements are dropped again.
+    pub fn clear_eviction_hook(&mut self) {
+        self.on_evict = None;
+    }
+
+    /// The total number of elements evicted by `overwrite` and `overwrite_evict`.
+    /// Elements removed by `read` or `clear` are not counted.
+    pub fn evicted(&self) -> u64 {
+        self.evicted
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Copy> CircularBuffer<T> {
+    /// Write as many elements from the front of `elements` as there is room for,
+    /// and return how many were written. A growable buffer grows to fit them all.
+    pub fn write_slice(&mut self, elements: &[T]) -> usize {
+        if self.growable {
+            self.grow(elements.len());
+        }
+        let count = elements.len().min(self.capacity() - self.len());
+        if count == 0 {
+            return 0;
+        }
+        // The free slots start at 'next' and may wrap around the end of the storage
+        let to_end = count.min(self.buffer.len() - self.next);
+        let (head, tail) = self.buffer.split_at_mut(self.next);
+        let slots = tail[..to_end].iter_mut().chain(&mut head[..count - to_end]);
+        for (slot, element) in slots.zip(&elements[..count]) {
+            slot.write(*element);
+        }
+        self.next = (self.next + count) % self.buffer.len();
         self.is_empty = false;
-        self.buffer[self.next] = element;
-        if self.is_full() {
-            self.oldest = (self.oldest + 1) % self.buffer.capacity();
+        count
+    }
+
+    /// Read the oldest elements into `buf` until it is full or the buffer is
+    /// empty, and return how many were read.
+    pub fn read_into(&mut self, buf: &mut [T]) -> usize {
+        let count = buf.len().min(self.len());
+        if count == 0 {
+            return 0;
         }
-        self.next = (self.next + 1) % self.buffer.len();
+        let (first, second) = self.as_slices();
+        let from_first = count.min(first.len());
+        buf[..from_first].copy_from_slice(&first[..from_first]);
+        buf[from_first..count].copy_from_slice(&second[..count - from_first]);
+        self.discard(count);
+        count
+    }
+
+    /// Remove the `count` oldest elements, or every element if there are fewer.
+    fn discard(&mut self, count: usize) {
+        let count = count.min(self.len());
+        if count == 0 {
+            return;
+        }
+        // The elements are 'Copy', so the slots can be left as they are
+        self.oldest = (self.oldest + count) % self.buffer.len();
+        if self.oldest == self.next {
+            self.is_empty = true;
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Index<usize> for CircularBuffer<T> {
+    type Output = T;
+
+    /// The element at position `index`, counting from the oldest element.
+    fn index(&self, index: usize) -> &T {
+        let len = self.len();
+        self.get(index).unwrap_or_else(|| {
+            panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                len, index
+            )
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> IndexMut<usize> for CircularBuffer<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len();
+        self.get_mut(index).unwrap_or_else(|| {
+            panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                len, index
+            )
+        })
+    }
+}
+
+/// Note that the inherent `write` and `read`, which take single elements, are
+/// found first by method calls, so call these through the traits, as in
+/// `io::Write::write(&mut buffer, bytes)`, or use `write_all` and friends.
+#[cfg(feature = "std")]
+impl io::Write for CircularBuffer<u8> {
+    /// Write as many bytes as there is room for. Returns `Ok(0)` when the buffer is full.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(self.write_slice(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl io::Read for CircularBuffer<u8> {
+    /// Drain bytes into `buf`. Returns `Ok(0)` when the buffer is empty.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        Ok(self.read_into(buf))
+    }
+}
+
+#[cfg(feature = "std")]
+impl io::BufRead for CircularBuffer<u8> {
+    /// The oldest bytes which are contiguous in the storage. The rest of the
+    /// bytes become available once these are consumed.
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(self.as_slices().0)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.discard(amt);
+    }
+}
+
+/// # Safety
+///
+/// Every slot must be initialized.
+unsafe fn assume_init_slice<T>(slots: &[MaybeUninit<T>]) -> &[T] {
+    // 'MaybeUninit<T>' has the same layout as 'T'
+    slice::from_raw_parts(slots.as_ptr() as *const T, slots.len())
+}
+
+/// # Safety
+///
+/// Every slot must be initialized.
+#[cfg(feature = "std")]
+unsafe fn assume_init_slice_mut<T>(slots: &mut [MaybeUninit<T>]) -> &mut [T] {
+    slice::from_raw_parts_mut(slots.as_mut_ptr() as *mut T, slots.len())
+}
+
+#[cfg(feature = "std")]
+impl<T> Drop for CircularBuffer<T> {
+    fn drop(&mut self) {
+        self.clear();
     }
 }
@@ -0,0 +1,171 @@
+//! A lock-free ring buffer for exactly one producer thread and one consumer thread.
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::Error;
+
+struct Ring<T> {
+    // The length is zero or a power of two, so that it divides 2^N and the
+    // counters below still pick the right slot after they wrap around.
+    buffer: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    // 'head' counts the elements read and 'tail' the elements written, so
+    // 'tail - head' elements are in the buffer, with the oldest at index
+    // 'head & (buffer.len() - 1)'. Only the consumer stores to 'head' and only
+    // the producer stores to 'tail'. Both wrap around on overflow.
+    head: AtomicUsize,
+    tail: AtomicUsize,
+}
+
+// SAFETY: A slot is only ever accessed by one side at a time: the producer owns
+// the slots from 'tail' up to 'head + capacity', the consumer owns the slots
+// from 'head' up to 'tail', and ownership is handed over with release/acquire
+// stores and loads of the counters.
+unsafe impl<T: Send> Sync for Ring<T> {}
+
+impl<T> Ring<T> {
+    fn slot(&self, counter: usize) -> *mut MaybeUninit<T> {
+        self.buffer[counter & (self.buffer.len() - 1)].get()
+    }
+}
+
+impl<T> Drop for Ring<T> {
+    fn drop(&mut self) {
+        let head = *self.head.get_mut();
+        let tail = *self.tail.get_mut();
+        let mut counter = head;
+        while counter != tail {
+            // SAFETY: Both halves are gone, and the slots from 'head' up to
+            // 'tail' hold elements which were written but never read.
+            unsafe { (*self.slot(counter)).assume_init_drop() };
+            counter = counter.wrapping_add(1);
+        }
+    }
+}
+
+/// The writing half of a lock-free single-producer single-consumer buffer.
+pub struct Producer<T> {
+    ring: Arc<Ring<T>>,
+}
+
+/// The reading half of a lock-free single-producer single-consumer buffer.
+pub struct Consumer<T> {
+    ring: Arc<Ring<T>>,
+}
+
+/// Create a lock-free buffer holding at least `capacity` elements, split into
+/// the halves for writing and reading. Each half can be sent to its own thread.
+///
+/// The capacity is rounded up to a power of two, which can nearly double the
+/// memory used: a capacity of 1025 allocates room for 2048 elements. The
+/// `capacity` of each half reports the rounded up capacity.
+pub fn spsc<T>(capacity: usize) -> (Producer<T>, Consumer<T>) {
+    with_counters(capacity, 0)
+}
+
+/// Create the halves of a buffer whose counters both start at `start`.
+fn with_counters<T>(capacity: usize, start: usize) -> (Producer<T>, Consumer<T>) {
+    let capacity = match capacity {
+        0 => 0,
+        _ => capacity
+            .checked_next_power_of_two()
+            .expect("capacity overflow"),
+    };
+    let ring = Arc::new(Ring {
+        buffer: (0..capacity)
+            .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+            .collect(),
+        head: AtomicUsize::new(start),
+        tail: AtomicUsize::new(start),
+    });
+    (
+        Producer {
+            ring: Arc::clone(&ring),
+        },
+        Consumer { ring },
+    )
+}
+
+impl<T> Producer<T> {
+    pub fn write(&mut self, element: T) -> Result<(), Error> {
+        let tail = self.ring.tail.load(Ordering::Relaxed);
+        // Acquire, so that the consumer is done with the slot before it is reused
+        let head = self.ring.head.load(Ordering::Acquire);
+        if tail.wrapping_sub(head) == self.ring.buffer.len() {
+            return Err(Error::FullBuffer);
+        }
+        // SAFETY: The buffer is not full, so the slot at 'tail' is not visible
+        // to the consumer until 'tail' is advanced below.
+        unsafe { (*self.ring.slot(tail)).write(element) };
+        self.ring
+            .tail
+            .store(tail.wrapping_add(1), Ordering::Release);
+        Ok(())
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.ring.buffer.len()
+    }
+}
+
+impl<T> Consumer<T> {
+    pub fn read(&mut self) -> Result<T, Error> {
+        let head = self.ring.head.load(Ordering::Relaxed);
+        // Acquire, so that the element written by the producer is visible
+        let tail = self.ring.tail.load(Ordering::Acquire);
+        if head == tail {
+            return Err(Error::EmptyBuffer);
+        }
+        // SAFETY: The buffer is not empty, so the slot at 'head' was initialized
+        // by the producer, and it is not reused until 'head' is advanced below.
+        let element = unsafe { (*self.ring.slot(head)).assume_init_read() };
+        self.ring
+            .head
+            .store(head.wrapping_add(1), Ordering::Release);
+        Ok(element)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.ring.buffer.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_wrap_around() {
+        let (mut producer, mut consumer) = with_counters(7, usize::MAX - 10);
+        let mut next_read = 0;
+        for item in 0..100 {
+            if producer.write(item).is_err() {
+                assert_eq!(Ok(next_read), consumer.read());
+                next_read += 1;
+                assert!(producer.write(item).is_ok());
+            }
+        }
+        while let Ok(item) = consumer.read() {
+            assert_eq!(next_read, item);
+            next_read += 1;
+        }
+        assert_eq!(100, next_read);
+    }
+
+    #[test]
+    fn counters_wrap_around_while_full() {
+        let (mut producer, mut consumer) = with_counters(4, usize::MAX - 1);
+        for item in 0..4 {
+            assert!(producer.write(item).is_ok());
+        }
+        assert_eq!(Err(Error::FullBuffer), producer.write(4));
+        assert_eq!(Ok(0), consumer.read());
+        assert!(producer.write(4).is_ok());
+        assert_eq!(Err(Error::FullBuffer), producer.write(5));
+        for item in 1..5 {
+            assert_eq!(Ok(item), consumer.read());
+        }
+        assert_eq!(Err(Error::EmptyBuffer), consumer.read());
+    }
+}
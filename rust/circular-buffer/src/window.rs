@@ -0,0 +1,164 @@
+//! A buffer of timestamped values covering a sliding window of time, with
+//! running aggregates over the values in the window.
+use crate::CircularBuffer;
+
+#[derive(Debug, PartialEq)]
+pub enum WindowError {
+    /// The timestamp is older than the newest one already in the window.
+    OutOfOrder,
+}
+
+/// Values keyed by timestamp, where only the values from the last `window`
+/// time units are kept.
+///
+/// Timestamps are plain numbers in whatever unit the caller picks, such as
+/// milliseconds, and must not decrease. The sum, mean, minimum and maximum are
+/// kept up to date as values come and go, in amortized constant time.
+pub struct TimeWindow {
+    window: u64,
+    // The values in the window, oldest first, with the sequence number each
+    // value was given when it was pushed
+    entries: CircularBuffer<(u64, u64, f64)>,
+    // Monotonic queues of '(sequence number, value)', oldest first. The values
+    // increase from the front of 'minima' and decrease from the front of
+    // 'maxima', so the front is the minimum or maximum of the whole window.
+    minima: CircularBuffer<(u64, f64)>,
+    maxima: CircularBuffer<(u64, f64)>,
+    sum: f64,
+    newest: Option<u64>,
+    pushed: u64,
+}
+
+impl TimeWindow {
+    /// Create a window which keeps the values pushed within the last `window`
+    /// time units.
+    pub fn new(window: u64) -> Self {
+        TimeWindow {
+            window,
+            entries: CircularBuffer::growable(0),
+            minima: CircularBuffer::growable(0),
+            maxima: CircularBuffer::growable(0),
+            sum: 0.0,
+            newest: None,
+            pushed: 0,
+        }
+    }
+
+    /// Add a value at `timestamp`, which also moves the window up to it.
+    pub fn push(&mut self, timestamp: u64, value: f64) -> Result<(), WindowError> {
+        if self.newest.is_some_and(|newest| timestamp < newest) {
+            return Err(WindowError::OutOfOrder);
+        }
+        self.advance(timestamp);
+        let seq = self.pushed;
+        self.pushed += 1;
+        append(&mut self.entries, (timestamp, seq, value));
+        self.sum += value;
+        // A value can never be the minimum again once a smaller or equal newer
+        // value has arrived, and likewise for the maximum
+        while self
+            .minima
+            .peek_newest()
+            .is_ok_and(|&(_, min)| min >= value)
+        {
+            let _ = self.minima.read_newest();
+        }
+        append(&mut self.minima, (seq, value));
+        while self
+            .maxima
+            .peek_newest()
+            .is_ok_and(|&(_, max)| max <= value)
+        {
+            let _ = self.maxima.read_newest();
+        }
+        append(&mut self.maxima, (seq, value));
+        Ok(())
+    }
+
+    /// Move the window up to `now`, evicting the values which are `window`
+    /// time units old or older.
+    pub fn advance(&mut self, now: u64) {
+        let now = self.newest.map_or(now, |newest| newest.max(now));
+        self.newest = Some(now);
+        while let Ok(&(timestamp, seq, value)) = self.entries.peek() {
+            if now - timestamp < self.window {
+                break;
+            }
+            let _ = self.entries.read();
+            self.sum -= value;
+            if self.minima.peek().is_ok_and(|&(min_seq, _)| min_seq == seq) {
+                let _ = self.minima.read();
+            }
+            if self.maxima.peek().is_ok_and(|&(max_seq, _)| max_seq == seq) {
+                let _ = self.maxima.read();
+            }
+        }
+        if self.entries.is_empty() {
+            // Start afresh, rather than keep the rounding errors of the running sum
+            self.sum = 0.0;
+        }
+    }
+
+    /// The length of the window, in time units.
+    pub fn window(&self) -> u64 {
+        self.window
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The values in the window, oldest first, with their timestamps.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (u64, f64)> + '_ {
+        self.entries
+            .iter()
+            .map(|&(timestamp, _seq, value)| (timestamp, value))
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.sum / self.len() as f64)
+        }
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.minima.peek().ok().map(|&(_, min)| min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.maxima.peek().ok().map(|&(_, max)| max)
+    }
+
+    /// The value at `percentile` (from 0 to 100) of the values in the window,
+    /// by the nearest-rank method: the smallest value which at least that
+    /// percentage of the values are less than or equal to.
+    ///
+    /// Unlike the other aggregates, this takes time linear in the number of values.
+    pub fn percentile(&self, percentile: f64) -> Option<f64> {
+        if self.is_empty() || !(0.0..=100.0).contains(&percentile) {
+            return None;
+        }
+        let mut values: Vec<f64> = self.iter().map(|(_timestamp, value)| value).collect();
+        let rank = (percentile / 100.0 * values.len() as f64).ceil() as usize;
+        let index = rank.max(1) - 1;
+        let (_, value, _) = values.select_nth_unstable_by(index, f64::total_cmp);
+        Some(*value)
+    }
+}
+
+/// Write to one of the growable buffers, which make room instead of failing.
+fn append<T>(buffer: &mut CircularBuffer<T>, element: T) {
+    buffer
+        .write(element)
+        .expect("growable buffers are never full");
+}
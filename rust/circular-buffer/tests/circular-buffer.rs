@@ -1,139 +1,878 @@
-use circular_buffer::{CircularBuffer, Error};
+use circular_buffer::{
+    spsc, BlockingCircularBuffer, CircularBuffer, Error, ShrinkPolicy, StaticCircularBuffer,
+    TimeWindow, WindowError,
+};
+use std::cell::Cell;
+use std::io::{BufRead, Read, Write};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// An element without a `Default` impl which counts how many times it is dropped.
+struct DropCounter<'a> {
+    drops: &'a Cell<usize>,
+}
+
+impl Drop for DropCounter<'_> {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
+
+macro_rules! new_circular_buffer {
+    ($capacity:expr) => {
+        CircularBuffer::new($capacity)
+    };
+    ($t:ty, $capacity:expr) => {
+        CircularBuffer::<$t>::new($capacity)
+    };
+}
+
+macro_rules! new_static_buffer {
+    ($capacity:expr) => {
+        StaticCircularBuffer::<_, $capacity>::new()
+    };
+    ($t:ty, $capacity:expr) => {
+        StaticCircularBuffer::<$t, $capacity>::new()
+    };
+}
+
+/// The tests for the behaviour shared by every buffer, with `$new!(capacity)`
+/// or `$new!(type, capacity)` creating the buffer under test.
+macro_rules! shared_buffer_tests {
+    ($name:ident, $new:ident) => {
+        mod $name {
+            use super::*;
+
+            #[test]
+            fn error_on_read_empty_buffer() {
+                let mut buffer = $new!(char, 1);
+                assert_eq!(Err(Error::EmptyBuffer), buffer.read());
+            }
+
+            #[test]
+            fn can_read_item_just_written() {
+                let mut buffer = $new!(1);
+                assert!(buffer.write('1').is_ok());
+                assert_eq!(Ok('1'), buffer.read());
+            }
+
+            #[test]
+            fn each_item_may_only_be_read_once() {
+                let mut buffer = $new!(1);
+                assert!(buffer.write('1').is_ok());
+                assert_eq!(Ok('1'), buffer.read());
+                assert_eq!(Err(Error::EmptyBuffer), buffer.read());
+            }
+
+            #[test]
+            fn items_are_read_in_the_order_they_are_written() {
+                let mut buffer = $new!(2);
+                assert!(buffer.write('1').is_ok());
+                assert!(buffer.write('2').is_ok());
+                assert_eq!(Ok('1'), buffer.read());
+                assert_eq!(Ok('2'), buffer.read());
+                assert_eq!(Err(Error::EmptyBuffer), buffer.read());
+            }
+
+            #[test]
+            fn full_buffer_cant_be_written_to() {
+                let mut buffer = $new!(1);
+                assert!(buffer.write('1').is_ok());
+                assert_eq!(Err(Error::FullBuffer), buffer.write('2'));
+            }
+
+            #[test]
+            fn read_frees_up_capacity_for_another_write() {
+                let mut buffer = $new!(1);
+                assert!(buffer.write('1').is_ok());
+                assert_eq!(Ok('1'), buffer.read());
+                assert!(buffer.write('2').is_ok());
+                assert_eq!(Ok('2'), buffer.read());
+            }
+
+            #[test]
+            fn read_position_is_maintained_even_across_multiple_writes() {
+                let mut buffer = $new!(3);
+                assert!(buffer.write('1').is_ok());
+                assert!(buffer.write('2').is_ok());
+                assert_eq!(Ok('1'), buffer.read());
+                assert!(buffer.write('3').is_ok());
+                assert_eq!(Ok('2'), buffer.read());
+                assert_eq!(Ok('3'), buffer.read());
+            }
+
+            #[test]
+            fn items_cleared_out_of_buffer_cant_be_read() {
+                let mut buffer = $new!(1);
+                assert!(buffer.write('1').is_ok());
+                buffer.clear();
+                assert_eq!(Err(Error::EmptyBuffer), buffer.read());
+            }
+
+            #[test]
+            fn clear_frees_up_capacity_for_another_write() {
+                let mut buffer = $new!(1);
+                assert!(buffer.write('1').is_ok());
+                buffer.clear();
+                assert!(buffer.write('2').is_ok());
+                assert_eq!(Ok('2'), buffer.read());
+            }
+
+            #[test]
+            fn clear_does_nothing_on_empty_buffer() {
+                let mut buffer = $new!(1);
+                buffer.clear();
+                assert!(buffer.write('1').is_ok());
+                assert_eq!(Ok('1'), buffer.read());
+            }
+
+            #[test]
+            fn overwrite_acts_like_write_on_non_full_buffer() {
+                let mut buffer = $new!(2);
+                assert!(buffer.write('1').is_ok());
+                buffer.overwrite('2');
+                assert_eq!(Ok('1'), buffer.read());
+                assert_eq!(Ok('2'), buffer.read());
+                assert_eq!(Err(Error::EmptyBuffer), buffer.read());
+            }
+
+            #[test]
+            fn overwrite_replaces_the_oldest_item_on_full_buffer() {
+                let mut buffer = $new!(2);
+                assert!(buffer.write('1').is_ok());
+                assert!(buffer.write('2').is_ok());
+                buffer.overwrite('A');
+                assert_eq!(Ok('2'), buffer.read());
+                assert_eq!(Ok('A'), buffer.read());
+            }
+
+            #[test]
+            fn overwrite_replaces_the_oldest_item_remaining_in_buffer_following_a_read() {
+                let mut buffer = $new!(3);
+                assert!(buffer.write('1').is_ok());
+                assert!(buffer.write('2').is_ok());
+                assert!(buffer.write('3').is_ok());
+                assert_eq!(Ok('1'), buffer.read());
+                assert!(buffer.write('4').is_ok());
+                buffer.overwrite('5');
+                assert_eq!(Ok('3'), buffer.read());
+                assert_eq!(Ok('4'), buffer.read());
+                assert_eq!(Ok('5'), buffer.read());
+            }
+
+            #[test]
+            fn integer_buffer() {
+                let mut buffer = $new!(2);
+                assert!(buffer.write(1).is_ok());
+                assert!(buffer.write(2).is_ok());
+                assert_eq!(Ok(1), buffer.read());
+                assert!(buffer.write(-1).is_ok());
+                assert_eq!(Ok(2), buffer.read());
+                assert_eq!(Ok(-1), buffer.read());
+                assert_eq!(Err(Error::EmptyBuffer), buffer.read());
+            }
+
+            #[test]
+            fn string_buffer() {
+                let mut buffer = $new!(2);
+                buffer.write("".to_string()).unwrap();
+                buffer.write("Testing".to_string()).unwrap();
+                assert_eq!(0, buffer.read().unwrap().len());
+                assert_eq!(Ok("Testing".to_string()), buffer.read());
+            }
+
+            #[test]
+            fn elements_do_not_need_a_default() {
+                let drops = Cell::new(0);
+                let mut buffer = $new!(2);
+                assert!(buffer.write(DropCounter { drops: &drops }).is_ok());
+                assert!(buffer.read().is_ok());
+                assert_eq!(1, drops.get());
+            }
+
+            #[test]
+            fn read_element_is_not_dropped_by_the_buffer() {
+                let drops = Cell::new(0);
+                let mut buffer = $new!(1);
+                assert!(buffer.write(DropCounter { drops: &drops }).is_ok());
+                let element = buffer.read().ok();
+                drop(buffer);
+                assert_eq!(0, drops.get());
+                drop(element);
+                assert_eq!(1, drops.get());
+            }
+
+            #[test]
+            fn overwrite_drops_the_replaced_element() {
+                let drops = Cell::new(0);
+                let mut buffer = $new!(2);
+                buffer.overwrite(DropCounter { drops: &drops });
+                buffer.overwrite(DropCounter { drops: &drops });
+                assert_eq!(0, drops.get());
+                buffer.overwrite(DropCounter { drops: &drops });
+                assert_eq!(1, drops.get());
+            }
+
+            #[test]
+            fn clear_drops_every_element_once() {
+                let drops = Cell::new(0);
+                let mut buffer = $new!(3);
+                for _ in 0..3 {
+                    assert!(buffer.write(DropCounter { drops: &drops }).is_ok());
+                }
+                assert!(buffer.read().is_ok());
+                buffer.clear();
+                assert_eq!(3, drops.get());
+                drop(buffer);
+                assert_eq!(3, drops.get());
+            }
+
+            #[test]
+            fn dropping_the_buffer_drops_the_remaining_elements() {
+                let drops = Cell::new(0);
+                let mut buffer = $new!(3);
+                // Wrap around so that the remaining elements straddle the end of the storage
+                for _ in 0..3 {
+                    assert!(buffer.write(DropCounter { drops: &drops }).is_ok());
+                }
+                assert!(buffer.read().is_ok());
+                assert!(buffer.read().is_ok());
+                assert!(buffer.write(DropCounter { drops: &drops }).is_ok());
+                assert_eq!(2, drops.get());
+                drop(buffer);
+                assert_eq!(4, drops.get());
+            }
+        }
+    };
+}
+
+shared_buffer_tests!(heap_buffer, new_circular_buffer);
+shared_buffer_tests!(static_buffer, new_static_buffer);
 
 #[test]
-fn error_on_read_empty_buffer() {
-    let mut buffer = CircularBuffer::<char>::new(1);
-    assert_eq!(Err(Error::EmptyBuffer), buffer.read());
+fn spsc_reads_items_in_the_order_they_are_written() {
+    let (mut producer, mut consumer) = spsc(2);
+    assert_eq!(Err(Error::EmptyBuffer), consumer.read());
+    assert!(producer.write('1').is_ok());
+    assert!(producer.write('2').is_ok());
+    assert_eq!(Err(Error::FullBuffer), producer.write('3'));
+    assert_eq!(Ok('1'), consumer.read());
+    assert!(producer.write('3').is_ok());
+    assert_eq!(Ok('2'), consumer.read());
+    assert_eq!(Ok('3'), consumer.read());
+    assert_eq!(Err(Error::EmptyBuffer), consumer.read());
 }
 
 #[test]
-fn can_read_item_just_written() {
-    let mut buffer = CircularBuffer::new(1);
-    assert!(buffer.write('1').is_ok());
-    assert_eq!(Ok('1'), buffer.read());
+fn spsc_with_zero_capacity_is_always_full() {
+    let (mut producer, mut consumer) = spsc(0);
+    assert_eq!(Err(Error::FullBuffer), producer.write('1'));
+    assert_eq!(Err(Error::EmptyBuffer), consumer.read());
 }
 
 #[test]
-fn each_item_may_only_be_read_once() {
-    let mut buffer = CircularBuffer::new(1);
-    assert!(buffer.write('1').is_ok());
-    assert_eq!(Ok('1'), buffer.read());
-    assert_eq!(Err(Error::EmptyBuffer), buffer.read());
+fn spsc_capacity_is_rounded_up_to_a_power_of_two() {
+    let (producer, consumer) = spsc::<char>(3);
+    assert_eq!(4, producer.capacity());
+    assert_eq!(4, consumer.capacity());
+    assert_eq!(8, spsc::<char>(8).0.capacity());
+    assert_eq!(0, spsc::<char>(0).0.capacity());
 }
 
 #[test]
-fn items_are_read_in_the_order_they_are_written() {
-    let mut buffer = CircularBuffer::new(2);
-    assert!(buffer.write('1').is_ok());
-    assert!(buffer.write('2').is_ok());
-    assert_eq!(Ok('1'), buffer.read());
-    assert_eq!(Ok('2'), buffer.read());
-    assert_eq!(Err(Error::EmptyBuffer), buffer.read());
+fn spsc_drops_unread_elements() {
+    let drops = Cell::new(0);
+    let (mut producer, mut consumer) = spsc(3);
+    for _ in 0..3 {
+        assert!(producer.write(DropCounter { drops: &drops }).is_ok());
+    }
+    assert!(consumer.read().is_ok());
+    assert_eq!(1, drops.get());
+    drop(producer);
+    assert_eq!(1, drops.get());
+    drop(consumer);
+    assert_eq!(3, drops.get());
 }
 
 #[test]
-fn full_buffer_cant_be_written_to() {
-    let mut buffer = CircularBuffer::new(1);
+fn spsc_halves_move_between_threads_without_loss_or_duplication() {
+    // Few enough items for Miri, while still wrapping around many times
+    const ITEMS: usize = if cfg!(miri) { 1_000 } else { 200_000 };
+    let (mut producer, mut consumer) = spsc(7);
+    let writer = thread::spawn(move || {
+        for i in 0..ITEMS {
+            let mut item = Box::new(i);
+            loop {
+                match producer.write(item) {
+                    Ok(()) => break,
+                    Err(Error::FullBuffer) => {
+                        thread::yield_now();
+                        item = Box::new(i);
+                    }
+                    Err(err) => panic!("unexpected error {:?}", err),
+                }
+            }
+        }
+    });
+    let reader = thread::spawn(move || {
+        let mut expected = 0;
+        while expected < ITEMS {
+            match consumer.read() {
+                Ok(item) => {
+                    assert_eq!(expected, *item);
+                    expected += 1;
+                }
+                Err(_) => thread::yield_now(),
+            }
+        }
+        assert_eq!(Err(Error::EmptyBuffer), consumer.read());
+    });
+    writer.join().unwrap();
+    reader.join().unwrap();
+}
+
+#[test]
+fn blocking_buffer_times_out() {
+    let buffer = BlockingCircularBuffer::new(1);
+    assert_eq!(
+        Err(Error::EmptyBuffer),
+        buffer.read_timeout(Duration::from_millis(10))
+    );
     assert!(buffer.write('1').is_ok());
-    assert_eq!(Err(Error::FullBuffer), buffer.write('2'));
+    assert_eq!(
+        Err(Error::FullBuffer),
+        buffer.write_timeout('2', Duration::from_millis(10))
+    );
+    assert_eq!(Ok('1'), buffer.read_timeout(Duration::from_millis(10)));
 }
 
 #[test]
-fn read_frees_up_capacity_for_another_write() {
-    let mut buffer = CircularBuffer::new(1);
+fn blocking_read_waits_for_a_write() {
+    let buffer = Arc::new(BlockingCircularBuffer::new(1));
+    let reader = {
+        let buffer = Arc::clone(&buffer);
+        thread::spawn(move || buffer.read_blocking())
+    };
+    thread::sleep(Duration::from_millis(10));
+    assert!(buffer.write_blocking('1').is_ok());
+    assert_eq!(Ok('1'), reader.join().unwrap());
+}
+
+#[test]
+fn blocking_write_waits_for_a_read() {
+    let buffer = Arc::new(BlockingCircularBuffer::new(1));
     assert!(buffer.write('1').is_ok());
-    assert_eq!(Ok('1'), buffer.read());
-    assert!(buffer.write('2').is_ok());
+    let writer = {
+        let buffer = Arc::clone(&buffer);
+        thread::spawn(move || buffer.write_blocking('2'))
+    };
+    thread::sleep(Duration::from_millis(10));
+    assert_eq!(Ok('1'), buffer.read_blocking());
+    assert_eq!(Ok(()), writer.join().unwrap());
     assert_eq!(Ok('2'), buffer.read());
 }
 
 #[test]
-fn read_position_is_maintained_even_across_multiple_writes() {
-    let mut buffer = CircularBuffer::new(3);
+fn close_wakes_all_waiters() {
+    let buffer = Arc::new(BlockingCircularBuffer::<char>::new(1));
+    let readers: Vec<_> = (0..3)
+        .map(|_| {
+            let buffer = Arc::clone(&buffer);
+            thread::spawn(move || buffer.read_blocking())
+        })
+        .collect();
+    thread::sleep(Duration::from_millis(10));
+    buffer.close();
+    for reader in readers {
+        assert_eq!(Err(Error::Closed), reader.join().unwrap());
+    }
+    assert_eq!(Err(Error::Closed), buffer.write('1'));
+}
+
+#[test]
+fn closed_buffer_can_still_be_drained() {
+    let buffer = BlockingCircularBuffer::new(2);
     assert!(buffer.write('1').is_ok());
-    assert!(buffer.write('2').is_ok());
+    buffer.close();
+    assert!(buffer.is_closed());
+    assert_eq!(Ok('1'), buffer.read_blocking());
+    assert_eq!(Err(Error::Closed), buffer.read_blocking());
+}
+
+#[test]
+fn blocking_buffer_with_many_producers_and_consumers() {
+    const PRODUCERS: usize = 4;
+    const ITEMS: usize = if cfg!(miri) { 50 } else { 5_000 };
+    let buffer = Arc::new(BlockingCircularBuffer::new(3));
+    let producers: Vec<_> = (0..PRODUCERS)
+        .map(|p| {
+            let buffer = Arc::clone(&buffer);
+            thread::spawn(move || {
+                for i in 0..ITEMS {
+                    buffer.write_blocking(p * ITEMS + i).unwrap();
+                }
+            })
+        })
+        .collect();
+    let consumers: Vec<_> = (0..3)
+        .map(|_| {
+            let buffer = Arc::clone(&buffer);
+            thread::spawn(move || {
+                let mut items = Vec::new();
+                while let Ok(item) = buffer.read_blocking() {
+                    items.push(item);
+                }
+                items
+            })
+        })
+        .collect();
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    buffer.close();
+    let mut items: Vec<usize> = consumers
+        .into_iter()
+        .flat_map(|consumer| consumer.join().unwrap())
+        .collect();
+    items.sort_unstable();
+    assert_eq!(items, (0..PRODUCERS * ITEMS).collect::<Vec<_>>());
+}
+
+/// A buffer of capacity 4 holding '3', '4', '5', wrapped around the end of its storage.
+fn wrapped_buffer() -> CircularBuffer<char> {
+    let mut buffer = CircularBuffer::new(4);
+    for c in ['1', '2', '3', '4'] {
+        assert!(buffer.write(c).is_ok());
+    }
     assert_eq!(Ok('1'), buffer.read());
-    assert!(buffer.write('3').is_ok());
     assert_eq!(Ok('2'), buffer.read());
-    assert_eq!(Ok('3'), buffer.read());
+    assert!(buffer.write('5').is_ok());
+    buffer
 }
 
 #[test]
-fn items_cleared_out_of_buffer_cant_be_read() {
-    let mut buffer = CircularBuffer::new(1);
-    assert!(buffer.write('1').is_ok());
+fn len_and_capacity() {
+    let mut buffer = wrapped_buffer();
+    assert_eq!(3, buffer.len());
+    assert_eq!(4, buffer.capacity());
+    assert!(!buffer.is_full());
+    assert!(buffer.write('6').is_ok());
+    assert!(buffer.is_full());
     buffer.clear();
+    assert_eq!(0, buffer.len());
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn peek_does_not_remove_elements() {
+    let buffer = wrapped_buffer();
+    assert_eq!(Ok(&'3'), buffer.peek());
+    assert_eq!(Ok(&'5'), buffer.peek_newest());
+    assert_eq!(3, buffer.len());
+    let empty = CircularBuffer::<char>::new(1);
+    assert_eq!(Err(Error::EmptyBuffer), empty.peek());
+    assert_eq!(Err(Error::EmptyBuffer), empty.peek_newest());
+}
+
+#[test]
+fn iterate_from_oldest_to_newest() {
+    let mut buffer = wrapped_buffer();
+    assert_eq!(
+        vec!['3', '4', '5'],
+        buffer.iter().copied().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec!['5', '4', '3'],
+        buffer.iter().rev().copied().collect::<Vec<_>>()
+    );
+    for c in buffer.iter_mut() {
+        *c = (*c as u8 + 1) as char;
+    }
+    assert_eq!(
+        vec!['4', '5', '6'],
+        buffer.iter().copied().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn index_counts_from_the_oldest_element() {
+    let mut buffer = wrapped_buffer();
+    assert_eq!('3', buffer[0]);
+    assert_eq!('5', buffer[2]);
+    buffer[1] = 'X';
+    assert_eq!(Some(&'X'), buffer.get(1));
+    assert_eq!(None, buffer.get(3));
+}
+
+#[test]
+#[should_panic(expected = "index out of bounds")]
+fn index_past_the_newest_element_panics() {
+    let buffer = wrapped_buffer();
+    let _ = buffer[3];
+}
+
+#[test]
+fn as_slices_splits_at_the_wrap_around() {
+    let buffer = wrapped_buffer();
+    assert_eq!((&['3', '4'][..], &['5'][..]), buffer.as_slices());
+    let mut unwrapped = CircularBuffer::new(3);
+    assert!(unwrapped.write(1).is_ok());
+    assert!(unwrapped.write(2).is_ok());
+    assert_eq!((&[1, 2][..], &[][..]), unwrapped.as_slices());
+}
+
+#[test]
+fn write_slice_writes_as_much_as_fits() {
+    let mut buffer = wrapped_buffer();
+    assert_eq!(1, buffer.write_slice(&['6', '7']));
+    assert_eq!(0, buffer.write_slice(&['7']));
+    assert_eq!(
+        vec!['3', '4', '5', '6'],
+        buffer.iter().copied().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn read_into_drains_across_the_wrap_around() {
+    let mut buffer = wrapped_buffer();
+    let mut out = ['-'; 2];
+    assert_eq!(2, buffer.read_into(&mut out));
+    assert_eq!(['3', '4'], out);
+    assert_eq!(3, buffer.write_slice(&['6', '7', '8']));
+    let mut out = ['-'; 5];
+    assert_eq!(4, buffer.read_into(&mut out));
+    assert_eq!(['5', '6', '7', '8', '-'], out);
     assert_eq!(Err(Error::EmptyBuffer), buffer.read());
+    assert_eq!(0, buffer.read_into(&mut out));
 }
 
 #[test]
-fn clear_frees_up_capacity_for_another_write() {
-    let mut buffer = CircularBuffer::new(1);
-    assert!(buffer.write('1').is_ok());
-    buffer.clear();
-    assert!(buffer.write('2').is_ok());
-    assert_eq!(Ok('2'), buffer.read());
+fn io_write_accepts_as_many_bytes_as_fit() {
+    let mut buffer = CircularBuffer::<u8>::new(4);
+    assert_eq!(3, Write::write(&mut buffer, b"abc").unwrap());
+    assert_eq!(1, Write::write(&mut buffer, b"def").unwrap());
+    assert_eq!(0, Write::write(&mut buffer, b"ef").unwrap());
+    assert_eq!(
+        std::io::ErrorKind::WriteZero,
+        buffer.write_all(b"ef").unwrap_err().kind()
+    );
+    assert!(buffer.flush().is_ok());
 }
 
 #[test]
-fn clear_does_nothing_on_empty_buffer() {
-    let mut buffer = CircularBuffer::new(1);
-    buffer.clear();
-    assert!(buffer.write('1').is_ok());
-    assert_eq!(Ok('1'), buffer.read());
+fn io_read_drains_the_buffer() {
+    let mut buffer = CircularBuffer::<u8>::new(4);
+    buffer.write_all(b"abc").unwrap();
+    let mut out = [0; 2];
+    assert_eq!(2, Read::read(&mut buffer, &mut out).unwrap());
+    assert_eq!(b"ab", &out);
+    buffer.write_all(b"def").unwrap();
+    let mut rest = Vec::new();
+    assert_eq!(4, buffer.read_to_end(&mut rest).unwrap());
+    assert_eq!(b"cdef", &rest[..]);
 }
 
 #[test]
-fn overwrite_acts_like_write_on_non_full_buffer() {
+fn buf_read_exposes_the_contiguous_region() {
+    let mut buffer = CircularBuffer::<u8>::new(4);
+    buffer.write_all(b"abcd").unwrap();
+    buffer.consume(2);
+    buffer.write_all(b"e\n").unwrap();
+    assert_eq!(b"cd", buffer.fill_buf().unwrap());
+    let mut line = String::new();
+    assert_eq!(4, buffer.read_line(&mut line).unwrap());
+    assert_eq!("cde\n", line);
+    assert!(buffer.fill_buf().unwrap().is_empty());
+}
+
+#[test]
+fn buffer_works_as_a_bounded_pipe() {
+    let mut pipe = CircularBuffer::<u8>::new(5);
+    let input = b"the quick brown fox jumps over the lazy dog";
+    let mut output = Vec::new();
+    let mut written = 0;
+    while output.len() < input.len() {
+        written += Write::write(&mut pipe, &input[written..]).unwrap();
+        let mut chunk = [0; 3];
+        let read = Read::read(&mut pipe, &mut chunk).unwrap();
+        output.extend_from_slice(&chunk[..read]);
+    }
+    assert_eq!(&input[..], &output[..]);
+}
+
+#[test]
+fn overwrite_evict_returns_the_evicted_element() {
     let mut buffer = CircularBuffer::new(2);
-    assert!(buffer.write('1').is_ok());
-    buffer.overwrite('2');
-    assert_eq!(Ok('1'), buffer.read());
-    assert_eq!(Ok('2'), buffer.read());
-    assert_eq!(Err(Error::EmptyBuffer), buffer.read());
+    assert_eq!(None, buffer.overwrite_evict('1'));
+    assert_eq!(None, buffer.overwrite_evict('2'));
+    assert_eq!(Some('1'), buffer.overwrite_evict('3'));
+    assert_eq!(Some('2'), buffer.overwrite_evict('4'));
+    assert_eq!(2, buffer.evicted());
+    assert_eq!(Ok('3'), buffer.read());
+    assert_eq!(Ok('4'), buffer.read());
 }
 
 #[test]
-fn overwrite_replaces_the_oldest_item_on_full_buffer() {
+fn eviction_hook_receives_overwritten_elements() {
+    let spilled = Arc::new(Mutex::new(Vec::new()));
     let mut buffer = CircularBuffer::new(2);
-    assert!(buffer.write('1').is_ok());
-    assert!(buffer.write('2').is_ok());
-    buffer.overwrite('A');
-    assert_eq!(Ok('2'), buffer.read());
-    assert_eq!(Ok('A'), buffer.read());
+    {
+        let spilled = Arc::clone(&spilled);
+        buffer.set_eviction_hook(move |element| spilled.lock().unwrap().push(element));
+    }
+    for i in 1..=5 {
+        buffer.overwrite(i);
+    }
+    assert_eq!(vec![1, 2, 3], *spilled.lock().unwrap());
+    // The caller takes the element instead of the hook
+    assert_eq!(Some(4), buffer.overwrite_evict(6));
+    assert_eq!(vec![1, 2, 3], *spilled.lock().unwrap());
+    assert_eq!(4, buffer.evicted());
 }
 
 #[test]
-fn overwrite_replaces_the_oldest_item_remaining_in_buffer_following_a_read() {
-    let mut buffer = CircularBuffer::new(3);
-    assert!(buffer.write('1').is_ok());
-    assert!(buffer.write('2').is_ok());
-    assert!(buffer.write('3').is_ok());
+fn buffer_with_eviction_hook_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<CircularBuffer<u8>>();
+
+    let shared = Arc::new(std::sync::RwLock::new(CircularBuffer::new(1)));
+    shared.write().unwrap().set_eviction_hook(|_: u8| {});
+    let reader = {
+        let shared = Arc::clone(&shared);
+        thread::spawn(move || shared.read().unwrap().evicted())
+    };
+    assert_eq!(0, reader.join().unwrap());
+}
+
+#[test]
+fn eviction_hook_does_not_need_to_be_sync() {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let total = Cell::new(0);
+    let mut buffer = CircularBuffer::new(1);
+    buffer.set_eviction_hook(move |element| {
+        total.set(total.get() + element);
+        sender.send(total.get()).unwrap();
+    });
+    for i in 1..=4 {
+        buffer.overwrite(i);
+    }
+    assert_eq!(vec![1, 3, 6], receiver.try_iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn reads_and_clears_are_not_evictions() {
+    let mut buffer = CircularBuffer::new(2);
+    buffer.set_eviction_hook(|_: char| panic!("nothing should be evicted"));
+    buffer.overwrite('1');
     assert_eq!(Ok('1'), buffer.read());
-    assert!(buffer.write('4').is_ok());
+    buffer.overwrite('2');
+    buffer.clear();
+    assert_eq!(0, buffer.evicted());
+    buffer.clear_eviction_hook();
+    buffer.overwrite('3');
+    buffer.overwrite('4');
     buffer.overwrite('5');
-    assert_eq!(Ok('3'), buffer.read());
-    assert_eq!(Ok('4'), buffer.read());
+    assert_eq!(1, buffer.evicted());
+}
+
+#[test]
+fn zero_capacity_is_an_error_instead_of_a_panic() {
+    let mut buffer = CircularBuffer::new(0);
+    assert_eq!(Err(Error::ZeroCapacity), buffer.write('1'));
+    assert_eq!(Err(Error::EmptyBuffer), buffer.read());
+    assert_eq!(Some('1'), buffer.overwrite_evict('1'));
+    assert_eq!(0, buffer.write_slice(&['1']));
+    assert_eq!(
+        Err(Error::ZeroCapacity),
+        wrapped_buffer().resize(0, ShrinkPolicy::DropOldest)
+    );
+}
+
+#[test]
+fn growing_keeps_the_elements_in_order() {
+    let mut buffer = wrapped_buffer();
+    assert!(buffer.resize(6, ShrinkPolicy::Reject).is_ok());
+    assert_eq!(6, buffer.capacity());
+    for c in ['6', '7', '8'] {
+        assert!(buffer.write(c).is_ok());
+    }
+    assert_eq!(Err(Error::FullBuffer), buffer.write('9'));
+    assert_eq!(
+        vec!['3', '4', '5', '6', '7', '8'],
+        buffer.iter().copied().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn shrinking_drops_the_oldest_elements() {
+    let mut buffer = wrapped_buffer();
+    assert!(buffer.resize(2, ShrinkPolicy::DropOldest).is_ok());
+    assert_eq!(2, buffer.capacity());
+    assert_eq!(vec!['4', '5'], buffer.iter().copied().collect::<Vec<_>>());
+    assert_eq!(0, buffer.evicted());
+}
+
+#[test]
+fn shrinking_can_evict_or_be_rejected() {
+    let mut buffer = wrapped_buffer();
+    assert_eq!(
+        Err(Error::WouldTruncate),
+        buffer.resize(2, ShrinkPolicy::Reject)
+    );
+    assert_eq!(4, buffer.capacity());
+    assert_eq!(3, buffer.len());
+
+    let spilled = Arc::new(Mutex::new(Vec::new()));
+    {
+        let spilled = Arc::clone(&spilled);
+        buffer.set_eviction_hook(move |element| spilled.lock().unwrap().push(element));
+    }
+    assert!(buffer.resize(1, ShrinkPolicy::EvictOldest).is_ok());
+    assert_eq!(vec!['3', '4'], *spilled.lock().unwrap());
+    assert_eq!(2, buffer.evicted());
     assert_eq!(Ok('5'), buffer.read());
 }
 
 #[test]
-fn integer_buffer() {
+fn growable_buffer_doubles_when_full() {
+    let mut buffer = CircularBuffer::growable(0);
+    assert!(buffer.is_growable());
+    for i in 0..5 {
+        assert!(buffer.write(i).is_ok());
+    }
+    assert_eq!(8, buffer.capacity());
+    buffer.overwrite(5);
+    assert_eq!(0, buffer.evicted());
+    assert_eq!(
+        10,
+        buffer.write_slice(&[6, 7, 8, 9, 10, 11, 12, 13, 14, 15])
+    );
+    assert_eq!(16, buffer.capacity());
+    assert_eq!(
+        (0..16).collect::<Vec<_>>(),
+        buffer.iter().copied().collect::<Vec<_>>()
+    );
+    buffer.set_growable(false);
+    assert_eq!(Err(Error::FullBuffer), buffer.write(16));
+}
+
+#[test]
+fn resize_moves_elements_without_dropping_them() {
+    let drops = Cell::new(0);
     let mut buffer = CircularBuffer::new(2);
-    assert!(buffer.write(1).is_ok());
-    assert!(buffer.write(2).is_ok());
-    assert_eq!(Ok(1), buffer.read());
-    assert!(buffer.write(-1).is_ok());
-    assert_eq!(Ok(2), buffer.read());
-    assert_eq!(Ok(-1), buffer.read());
+    assert!(buffer.write(DropCounter { drops: &drops }).is_ok());
+    assert!(buffer.write(DropCounter { drops: &drops }).is_ok());
+    assert!(buffer.resize(4, ShrinkPolicy::Reject).is_ok());
+    assert_eq!(0, drops.get());
+    assert!(buffer.resize(1, ShrinkPolicy::DropOldest).is_ok());
+    assert_eq!(1, drops.get());
+    drop(buffer);
+    assert_eq!(2, drops.get());
+}
+
+#[test]
+fn static_buffer_without_capacity() {
+    let mut buffer = StaticCircularBuffer::<char, 0>::new();
+    assert_eq!(0, buffer.capacity());
+    assert_eq!(Err(Error::ZeroCapacity), buffer.write('1'));
+    buffer.overwrite('1');
     assert_eq!(Err(Error::EmptyBuffer), buffer.read());
 }
 
 #[test]
-fn string_buffer() {
-    let mut buffer = CircularBuffer::new(2);
-    buffer.write("".to_string()).unwrap();
-    buffer.write("Testing".to_string()).unwrap();
-    assert_eq!(0, buffer.read().unwrap().len());
-    assert_eq!(Ok("Testing".to_string()), buffer.read());
+fn static_buffer_accessors() {
+    let mut buffer = StaticCircularBuffer::<char, 4>::default();
+    for c in ['1', '2', '3', '4'] {
+        assert!(buffer.write(c).is_ok());
+    }
+    assert!(buffer.is_full());
+    assert_eq!(Ok('1'), buffer.read());
+    assert_eq!(Ok('2'), buffer.read());
+    assert!(buffer.write('5').is_ok());
+    assert_eq!(3, buffer.len());
+    assert_eq!(Ok(&'3'), buffer.peek());
+    assert_eq!(Ok(&'5'), buffer.peek_newest());
+    assert_eq!((&['3', '4'][..], &['5'][..]), buffer.as_slices());
+    assert_eq!(
+        vec!['5', '4', '3'],
+        buffer.iter().rev().copied().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn static_buffer_can_be_built_in_a_const() {
+    const EMPTY: StaticCircularBuffer<u8, 16> = StaticCircularBuffer::new();
+    let mut buffer = EMPTY;
+    assert!(buffer.is_empty());
+    assert!(buffer.write(1).is_ok());
+    assert_eq!(1, buffer.len());
+}
+
+#[test]
+fn read_newest_takes_from_the_other_end() {
+    let mut buffer = wrapped_buffer();
+    assert_eq!(Ok('5'), buffer.read_newest());
+    assert_eq!(Ok('4'), buffer.read_newest());
+    assert!(buffer.write('6').is_ok());
+    assert_eq!(Ok('3'), buffer.read());
+    assert_eq!(Ok('6'), buffer.read_newest());
+    assert_eq!(Err(Error::EmptyBuffer), buffer.read_newest());
+}
+
+#[test]
+fn time_window_evicts_old_values() {
+    let mut window = TimeWindow::new(10);
+    assert!(window.push(0, 1.0).is_ok());
+    assert!(window.push(5, 2.0).is_ok());
+    assert!(window.push(9, 3.0).is_ok());
+    assert_eq!(3, window.len());
+    assert!(window.push(10, 4.0).is_ok());
+    assert_eq!(
+        vec![(5, 2.0), (9, 3.0), (10, 4.0)],
+        window.iter().collect::<Vec<_>>()
+    );
+    window.advance(100);
+    assert!(window.is_empty());
+    assert_eq!(None, window.mean());
+    assert_eq!(None, window.min());
+}
+
+#[test]
+fn time_window_rejects_timestamps_going_backwards() {
+    let mut window = TimeWindow::new(10);
+    assert!(window.push(5, 1.0).is_ok());
+    assert_eq!(Err(WindowError::OutOfOrder), window.push(4, 1.0));
+    assert!(window.push(5, 2.0).is_ok());
+}
+
+#[test]
+fn time_window_aggregates_slide_with_the_window() {
+    let mut window = TimeWindow::new(3);
+    let values = [5.0, 1.0, 4.0, 2.0, 8.0, 3.0, 3.0, 9.0];
+    for (t, &value) in values.iter().enumerate() {
+        assert!(window.push(t as u64, value).is_ok());
+        let in_window = &values[t.saturating_sub(2)..=t];
+        let min = in_window.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = in_window.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let sum: f64 = in_window.iter().sum();
+        assert_eq!(Some(min), window.min());
+        assert_eq!(Some(max), window.max());
+        assert_eq!(sum, window.sum());
+        assert_eq!(Some(sum / in_window.len() as f64), window.mean());
+    }
+}
+
+#[test]
+fn time_window_percentiles() {
+    let mut window = TimeWindow::new(1_000);
+    for (t, value) in [15.0, 20.0, 35.0, 40.0, 50.0].iter().enumerate() {
+        assert!(window.push(t as u64, *value).is_ok());
+    }
+    assert_eq!(Some(15.0), window.percentile(0.0));
+    assert_eq!(Some(20.0), window.percentile(30.0));
+    assert_eq!(Some(20.0), window.percentile(40.0));
+    assert_eq!(Some(35.0), window.percentile(50.0));
+    assert_eq!(Some(50.0), window.percentile(100.0));
+    assert_eq!(None, window.percentile(101.0));
 }
@@ -0,0 +1,296 @@
+//! Daily alarms on chosen days of the week, and a scheduler which says when
+//! they fire next and delivers the firings as time passes.
+use std::collections::BTreeMap;
+use std::iter;
+use std::ops::BitOr;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{Clock, Date, UtcOffset, Weekday, ZonedClock, NANOS_PER_DAY, SECONDS_PER_DAY};
+
+/// A set of days of the week.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct WeekdayMask(u8);
+
+impl WeekdayMask {
+    pub const NONE: WeekdayMask = WeekdayMask(0);
+    pub const WEEKDAYS: WeekdayMask = WeekdayMask(0b0011111);
+    pub const WEEKEND: WeekdayMask = WeekdayMask(0b1100000);
+    pub const EVERY_DAY: WeekdayMask = WeekdayMask(0b1111111);
+
+    pub fn contains(&self, weekday: Weekday) -> bool {
+        self.0 & (1 << weekday.index()) != 0
+    }
+
+    pub fn insert(&mut self, weekday: Weekday) {
+        self.0 |= 1 << weekday.index();
+    }
+
+    pub fn remove(&mut self, weekday: Weekday) {
+        self.0 &= !(1 << weekday.index());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl From<Weekday> for WeekdayMask {
+    fn from(weekday: Weekday) -> Self {
+        WeekdayMask(1 << weekday.index())
+    }
+}
+
+impl FromIterator<Weekday> for WeekdayMask {
+    fn from_iter<I: IntoIterator<Item = Weekday>>(weekdays: I) -> Self {
+        let mut mask = WeekdayMask::NONE;
+        for weekday in weekdays {
+            mask.insert(weekday);
+        }
+        mask
+    }
+}
+
+impl<T: Into<WeekdayMask>> BitOr<T> for WeekdayMask {
+    type Output = WeekdayMask;
+
+    fn bitor(self, other: T) -> WeekdayMask {
+        WeekdayMask(self.0 | other.into().0)
+    }
+}
+
+impl BitOr for Weekday {
+    type Output = WeekdayMask;
+
+    fn bitor(self, other: Weekday) -> WeekdayMask {
+        WeekdayMask::from(self) | other
+    }
+}
+
+/// Where a scheduler gets the current date and time of day.
+pub trait TimeSource {
+    fn now(&self) -> (Date, Clock);
+}
+
+/// The time from the system clock, at a fixed UTC offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemTimeSource {
+    offset: UtcOffset,
+}
+
+impl SystemTimeSource {
+    pub fn new(offset: UtcOffset) -> Self {
+        SystemTimeSource { offset }
+    }
+
+    pub fn utc() -> Self {
+        SystemTimeSource::new(UtcOffset::UTC)
+    }
+}
+
+impl TimeSource for SystemTimeSource {
+    fn now(&self) -> (Date, Clock) {
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let seconds = since_epoch.as_secs() as i64;
+        let utc = Clock::with_nanoseconds(
+            0,
+            0,
+            seconds.rem_euclid(SECONDS_PER_DAY) as i32,
+            since_epoch.subsec_nanos() as i64,
+        );
+        let (local, day_shift) = ZonedClock::utc(utc).to_offset(self.offset);
+        let date = Date::from_days_since_epoch(seconds.div_euclid(SECONDS_PER_DAY));
+        (date.add_days(day_shift as i64), local.clock())
+    }
+}
+
+/// A time source which only moves when told to, for tests. Clones share the
+/// same time, so one clone can be given to a scheduler and another kept to
+/// move the time.
+#[derive(Debug, Clone)]
+pub struct ManualTimeSource {
+    now: Arc<Mutex<(Date, Clock)>>,
+}
+
+impl ManualTimeSource {
+    pub fn new(date: Date, clock: Clock) -> Self {
+        ManualTimeSource {
+            now: Arc::new(Mutex::new((date, clock))),
+        }
+    }
+
+    pub fn set(&self, date: Date, clock: Clock) {
+        *self.now.lock().unwrap() = (date, clock);
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.lock().unwrap();
+        let (date, clock) = *now;
+        let nanos = clock.nanos_of_day() as u128 + duration.as_nanos();
+        let days = (nanos / NANOS_PER_DAY as u128) as i64;
+        *now = (date.add_days(days), clock + duration);
+    }
+}
+
+impl TimeSource for ManualTimeSource {
+    fn now(&self) -> (Date, Clock) {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AlarmId(u64);
+
+/// An alarm which fires at `clock` on each of `days`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alarm {
+    pub clock: Clock,
+    pub days: WeekdayMask,
+}
+
+/// One firing of an alarm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Occurrence {
+    pub alarm: AlarmId,
+    pub date: Date,
+    pub clock: Clock,
+}
+
+type Callback = Box<dyn FnMut(&Occurrence) + Send>;
+
+/// Alarms, and the callbacks and channels to deliver their firings to.
+///
+/// Nothing is delivered until `poll` is called, which fires every alarm due
+/// since the previous poll, or since the scheduler was created. Call it
+/// whenever convenient, such as once a second or after sleeping until
+/// `next_firing`.
+pub struct Scheduler<T: TimeSource> {
+    source: T,
+    alarms: BTreeMap<AlarmId, Alarm>,
+    next_id: u64,
+    last_poll: (Date, Clock),
+    callbacks: Vec<Callback>,
+    senders: Vec<Sender<Occurrence>>,
+}
+
+impl<T: TimeSource> Scheduler<T> {
+    pub fn new(source: T) -> Self {
+        let last_poll = source.now();
+        Scheduler {
+            source,
+            alarms: BTreeMap::new(),
+            next_id: 0,
+            last_poll,
+            callbacks: Vec::new(),
+            senders: Vec::new(),
+        }
+    }
+
+    /// Add an alarm at `clock` on `days`, such as `WeekdayMask::WEEKDAYS` or
+    /// `Weekday::Saturday | Weekday::Sunday`.
+    pub fn add(&mut self, clock: Clock, days: impl Into<WeekdayMask>) -> AlarmId {
+        let id = AlarmId(self.next_id);
+        self.next_id += 1;
+        let days = days.into();
+        self.alarms.insert(id, Alarm { clock, days });
+        id
+    }
+
+    /// Remove an alarm, returning it if it was there.
+    pub fn remove(&mut self, id: AlarmId) -> Option<Alarm> {
+        self.alarms.remove(&id)
+    }
+
+    pub fn alarm(&self, id: AlarmId) -> Option<&Alarm> {
+        self.alarms.get(&id)
+    }
+
+    /// The first firing after `clock` on `date`, not counting any at exactly
+    /// that time.
+    pub fn next_after(&self, date: Date, clock: Clock) -> Option<Occurrence> {
+        self.first_after(date, clock, AlarmId(u64::MAX))
+    }
+
+    /// Every firing after `clock` on `date`, in order. Alarms at the same time
+    /// fire in the order they were added.
+    pub fn occurrences_after(
+        &self,
+        date: Date,
+        clock: Clock,
+    ) -> impl Iterator<Item = Occurrence> + '_ {
+        iter::successors(self.next_after(date, clock), move |previous| {
+            self.first_after(previous.date, previous.clock, previous.alarm)
+        })
+    }
+
+    /// The first firing after the current time.
+    pub fn next_firing(&self) -> Option<Occurrence> {
+        let (date, clock) = self.source.now();
+        self.next_after(date, clock)
+    }
+
+    /// Every firing after the current time, in order.
+    pub fn upcoming(&self) -> impl Iterator<Item = Occurrence> + '_ {
+        let (date, clock) = self.source.now();
+        self.occurrences_after(date, clock)
+    }
+
+    /// Call `callback` with every firing delivered by `poll`.
+    pub fn on_fire(&mut self, callback: impl FnMut(&Occurrence) + Send + 'static) {
+        self.callbacks.push(Box::new(callback));
+    }
+
+    /// A channel which receives every firing delivered by `poll`, until the
+    /// receiver is dropped.
+    pub fn subscribe(&mut self) -> Receiver<Occurrence> {
+        let (sender, receiver) = mpsc::channel();
+        self.senders.push(sender);
+        receiver
+    }
+
+    /// Deliver every firing since the previous poll, up to and including the
+    /// current time, and return them. Nothing fires if the time source has
+    /// gone back.
+    pub fn poll(&mut self) -> Vec<Occurrence> {
+        let now = self.source.now();
+        if now <= self.last_poll {
+            return Vec::new();
+        }
+        let (date, clock) = self.last_poll;
+        let fired: Vec<Occurrence> = self
+            .occurrences_after(date, clock)
+            .take_while(|occurrence| (occurrence.date, occurrence.clock) <= now)
+            .collect();
+        self.last_poll = now;
+        for occurrence in &fired {
+            for callback in &mut self.callbacks {
+                callback(occurrence);
+            }
+            self.senders
+                .retain(|sender| sender.send(*occurrence).is_ok());
+        }
+        fired
+    }
+
+    /// The first firing after the alarm `after` fired at `clock` on `date`.
+    fn first_after(&self, date: Date, clock: Clock, after: AlarmId) -> Option<Occurrence> {
+        // Every alarm with any days fires within a week of any day, counting
+        // the rest of the first day and the start of the same day a week on
+        (0..=7).find_map(|days| {
+            let day = date.add_days(days);
+            self.alarms
+                .iter()
+                .filter(|(_, alarm)| alarm.days.contains(day.weekday()))
+                .filter(|(&id, alarm)| days > 0 || (alarm.clock, id) > (clock, after))
+                .min_by_key(|(&id, alarm)| (alarm.clock, id))
+                .map(|(&alarm, &Alarm { clock, .. })| Occurrence {
+                    alarm,
+                    date: day,
+                    clock,
+                })
+        })
+    }
+}
@@ -0,0 +1,245 @@
+//! Spans of the day between two clocks, and sets of them, for questions such
+//! as whether two opening hours overlap or when a day is free.
+use std::time::Duration;
+
+use crate::{Clock, NANOS_PER_DAY};
+
+/// The times from `start` up to, but not including, `end`, which wraps past
+/// midnight if `end` is before `start`, as in `22:00-06:00`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClockInterval {
+    start: Clock,
+    // Nanoseconds from 'start' to the end, up to a whole day
+    length: i64,
+}
+
+impl ClockInterval {
+    /// The interval from `start` forward to `end`, which is empty if they are
+    /// the same clock.
+    pub fn new(start: Clock, end: Clock) -> Self {
+        ClockInterval {
+            start,
+            length: (end - start).as_nanos() as i64,
+        }
+    }
+
+    /// The interval of `duration` from `start`, which covers the whole day if
+    /// `duration` is a day or longer.
+    pub fn with_duration(start: Clock, duration: Duration) -> Self {
+        ClockInterval {
+            start,
+            length: duration.as_nanos().min(NANOS_PER_DAY as u128) as i64,
+        }
+    }
+
+    /// The interval covering the whole day, from midnight to midnight.
+    pub fn whole_day() -> Self {
+        ClockInterval::with_duration(Clock::new(0, 0), Duration::from_nanos(NANOS_PER_DAY as u64))
+    }
+
+    pub fn start(&self) -> Clock {
+        self.start
+    }
+
+    pub fn end(&self) -> Clock {
+        self.start + self.duration()
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_nanos(self.length as u64)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    pub fn is_whole_day(&self) -> bool {
+        self.length == NANOS_PER_DAY
+    }
+
+    /// Whether the interval runs past midnight into the next day.
+    pub fn wraps_midnight(&self) -> bool {
+        self.start.nanos_of_day() + self.length > NANOS_PER_DAY
+    }
+
+    pub fn contains(&self, clock: Clock) -> bool {
+        ((clock - self.start).as_nanos() as i64) < self.length
+    }
+
+    pub fn overlaps(&self, other: &ClockInterval) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    /// The times in both intervals. Two intervals which both wrap past
+    /// midnight can meet in two separate spans, so this is a set.
+    pub fn intersection(&self, other: &ClockInterval) -> IntervalSet {
+        IntervalSet::from(*self).intersection(&IntervalSet::from(*other))
+    }
+
+    /// The times in either interval.
+    pub fn union(&self, other: &ClockInterval) -> IntervalSet {
+        IntervalSet::from_iter([*self, *other])
+    }
+
+    /// The interval as one or two spans of nanoseconds since midnight which do
+    /// not wrap, split at midnight if the interval wraps.
+    fn spans(&self) -> impl Iterator<Item = (i64, i64)> {
+        let start = self.start.nanos_of_day();
+        let end = start + self.length;
+        let (first, second) = if end > NANOS_PER_DAY {
+            ((start, NANOS_PER_DAY), (0, end - NANOS_PER_DAY))
+        } else {
+            ((start, end), (0, 0))
+        };
+        [first, second]
+            .into_iter()
+            .filter(|(start, end)| start < end)
+    }
+}
+
+/// Any number of intervals, kept merged so that the intervals in the set never
+/// overlap or touch.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct IntervalSet {
+    // Spans of nanoseconds since midnight, sorted, none of them empty, and
+    // with gaps between them. A span ending at midnight and one starting at
+    // midnight are both kept, and joined again when iterating.
+    spans: Vec<(i64, i64)>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        IntervalSet::default()
+    }
+
+    pub fn insert(&mut self, interval: ClockInterval) {
+        self.spans.extend(interval.spans());
+        self.spans.sort_unstable();
+        let mut merged: Vec<(i64, i64)> = Vec::with_capacity(self.spans.len());
+        for &(start, end) in &self.spans {
+            match merged.last_mut() {
+                Some((_, last_end)) if start <= *last_end => *last_end = end.max(*last_end),
+                _ => merged.push((start, end)),
+            }
+        }
+        self.spans = merged;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    pub fn contains(&self, clock: Clock) -> bool {
+        let nanos = clock.nanos_of_day();
+        self.spans
+            .iter()
+            .any(|&(start, end)| start <= nanos && nanos < end)
+    }
+
+    /// The times in either set.
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        let mut union = self.clone();
+        union.extend(other.iter());
+        union
+    }
+
+    /// The times in both sets.
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut spans = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.spans.len() && j < other.spans.len() {
+            let (a_start, a_end) = self.spans[i];
+            let (b_start, b_end) = other.spans[j];
+            let (start, end) = (a_start.max(b_start), a_end.min(b_end));
+            if start < end {
+                spans.push((start, end));
+            }
+            // Move past whichever span ends first, as it can meet no more spans
+            if a_end < b_end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { spans }
+    }
+
+    /// The total time covered by the set, up to a whole day.
+    pub fn duration(&self) -> Duration {
+        let nanos: i64 = self.spans.iter().map(|(start, end)| end - start).sum();
+        Duration::from_nanos(nanos as u64)
+    }
+
+    /// The times of the day not in the set.
+    pub fn complement(&self) -> IntervalSet {
+        let mut spans = Vec::with_capacity(self.spans.len() + 1);
+        let mut free_from = 0;
+        for &(start, end) in &self.spans {
+            if free_from < start {
+                spans.push((free_from, start));
+            }
+            free_from = end;
+        }
+        if free_from < NANOS_PER_DAY {
+            spans.push((free_from, NANOS_PER_DAY));
+        }
+        IntervalSet { spans }
+    }
+
+    /// The gaps between the intervals in the set which last at least
+    /// `min_duration`, in order from midnight. A gap which spans midnight is
+    /// one slot, and comes last.
+    pub fn free_slots(&self, min_duration: Duration) -> Vec<ClockInterval> {
+        self.complement()
+            .iter()
+            .filter(|slot| slot.duration() >= min_duration)
+            .collect()
+    }
+
+    /// The intervals in the set in order from midnight, except that an interval
+    /// which wraps past midnight comes last.
+    pub fn iter(&self) -> impl Iterator<Item = ClockInterval> + '_ {
+        let to_interval = |(start, end): (i64, i64)| {
+            ClockInterval::with_duration(
+                Clock::with_nanoseconds(0, 0, 0, start),
+                Duration::from_nanos((end - start) as u64),
+            )
+        };
+        // Join the span ending at midnight with the span starting at midnight
+        let wraps = self.spans.len() > 1
+            && self.spans[0].0 == 0
+            && self.spans[self.spans.len() - 1].1 == NANOS_PER_DAY;
+        let (middle, wrapping) = if wraps {
+            let (first, last) = (self.spans[0], self.spans[self.spans.len() - 1]);
+            (
+                &self.spans[1..self.spans.len() - 1],
+                Some((last.0, NANOS_PER_DAY + first.1)),
+            )
+        } else {
+            (&self.spans[..], None)
+        };
+        middle.iter().copied().chain(wrapping).map(to_interval)
+    }
+}
+
+impl From<ClockInterval> for IntervalSet {
+    fn from(interval: ClockInterval) -> Self {
+        IntervalSet::from_iter([interval])
+    }
+}
+
+impl Extend<ClockInterval> for IntervalSet {
+    fn extend<I: IntoIterator<Item = ClockInterval>>(&mut self, intervals: I) {
+        for interval in intervals {
+            self.insert(interval);
+        }
+    }
+}
+
+impl FromIterator<ClockInterval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = ClockInterval>>(intervals: I) -> Self {
+        let mut set = IntervalSet::new();
+        set.extend(intervals);
+        set
+    }
+}
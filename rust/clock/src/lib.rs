@@ -1,37 +1,382 @@
+use std::error::Error;
 use std::fmt;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::str::FromStr;
+use std::time::Duration;
 
-#[derive(Debug)]
+mod alarm;
+mod date;
+mod interval;
+mod zone;
+
+pub use alarm::{
+    Alarm, AlarmId, ManualTimeSource, Occurrence, Scheduler, SystemTimeSource, TimeSource,
+    WeekdayMask,
+};
+pub use date::{Date, Weekday};
+pub use interval::{ClockInterval, IntervalSet};
+pub use zone::{
+    ParseZoneError, ParseZoneErrorKind, TimeZone, Transition, TzData, UtcOffset, ZonedClock,
+};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+const NANOS_PER_DAY: i64 = SECONDS_PER_DAY * NANOS_PER_SECOND;
+
+/// Clocks are ordered from midnight to just before the next midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Clock {
     hours: i32,
     minutes: i32,
+    seconds: i32,
+    nanoseconds: u32,
 }
 
 impl Clock {
     pub fn new(hours: i32, minutes: i32) -> Self {
-        let mut hours = (hours + minutes / 60).rem_euclid(24);
-        if minutes < 0 {
-            hours = (hours - 1).rem_euclid(24)
-        }
-        let minutes = minutes.rem_euclid(60);
+        Clock::with_nanoseconds(hours, minutes, 0, 0)
+    }
+
+    pub fn with_seconds(hours: i32, minutes: i32, seconds: i32) -> Self {
+        Clock::with_nanoseconds(hours, minutes, seconds, 0)
+    }
+
+    /// Any of the parts may be negative or out of range, and carry over into
+    /// the larger parts, wrapping around midnight.
+    pub fn with_nanoseconds(hours: i32, minutes: i32, seconds: i32, nanoseconds: i64) -> Self {
+        let seconds = hours as i64 * 3600
+            + minutes as i64 * 60
+            + seconds as i64
+            + nanoseconds.div_euclid(NANOS_PER_SECOND);
+        let seconds = seconds.rem_euclid(SECONDS_PER_DAY) as i32;
         Clock {
-            hours: hours,
-            minutes: minutes,
+            hours: seconds / 3600,
+            minutes: seconds / 60 % 60,
+            seconds: seconds % 60,
+            nanoseconds: nanoseconds.rem_euclid(NANOS_PER_SECOND) as u32,
         }
     }
 
+    pub fn hours(&self) -> i32 {
+        self.hours
+    }
+
+    pub fn minutes(&self) -> i32 {
+        self.minutes
+    }
+
+    pub fn seconds(&self) -> i32 {
+        self.seconds
+    }
+
+    pub fn nanoseconds(&self) -> u32 {
+        self.nanoseconds
+    }
+
     pub fn add_minutes(&self, minutes: i32) -> Self {
-        Clock::new(self.hours, self.minutes + minutes)
+        Clock::with_nanoseconds(
+            self.hours,
+            self.minutes + minutes,
+            self.seconds,
+            self.nanoseconds as i64,
+        )
+    }
+
+    /// Add minutes as for `add_minutes`, also returning the number of days
+    /// carried: how many midnights were crossed, negative when going backwards.
+    pub fn add_minutes_with_carry(&self, minutes: i32) -> (Self, i32) {
+        let seconds = self.hours as i64 * 3600
+            + (self.minutes as i64 + minutes as i64) * 60
+            + self.seconds as i64;
+        let days = seconds.div_euclid(SECONDS_PER_DAY) as i32;
+        (self.add_minutes(minutes), days)
+    }
+
+    pub fn add_seconds(&self, seconds: i32) -> Self {
+        // Added as nanoseconds, which cannot overflow an i64 for any i32 seconds
+        Clock::with_nanoseconds(
+            self.hours,
+            self.minutes,
+            self.seconds,
+            self.nanoseconds as i64 + seconds as i64 * NANOS_PER_SECOND,
+        )
+    }
+
+    pub fn add_duration(&self, duration: Duration) -> Self {
+        // Whole days do not move the clock, so only the rest of the duration is added
+        let seconds = (duration.as_secs() % SECONDS_PER_DAY as u64) as i32;
+        Clock::with_nanoseconds(
+            self.hours,
+            self.minutes,
+            self.seconds + seconds,
+            self.nanoseconds as i64 + duration.subsec_nanos() as i64,
+        )
+    }
+
+    /// Nanoseconds since midnight.
+    fn nanos_of_day(&self) -> i64 {
+        let seconds = self.hours as i64 * 3600 + self.minutes as i64 * 60 + self.seconds as i64;
+        seconds * NANOS_PER_SECOND + self.nanoseconds as i64
+    }
+
+    /// Format in 12-hour time, such as `8:30 PM`, or `12:05:30 AM` if there are seconds.
+    pub fn to_12_hour_string(&self) -> String {
+        let meridiem = if self.hours < 12 { "AM" } else { "PM" };
+        let hours = match self.hours % 12 {
+            0 => 12,
+            hours => hours,
+        };
+        if self.seconds == 0 {
+            format!("{}:{:0>2} {}", hours, self.minutes, meridiem)
+        } else {
+            format!(
+                "{}:{:0>2}:{:0>2} {}",
+                hours, self.minutes, self.seconds, meridiem
+            )
+        }
     }
 }
 
-impl fmt::Display for Clock {
+impl Add<Duration> for Clock {
+    type Output = Clock;
+
+    fn add(self, duration: Duration) -> Clock {
+        self.add_duration(duration)
+    }
+}
+
+impl Sub<Duration> for Clock {
+    type Output = Clock;
+
+    fn sub(self, duration: Duration) -> Clock {
+        let nanos = (duration.as_nanos() % NANOS_PER_DAY as u128) as i64;
+        Clock::with_nanoseconds(0, 0, 0, self.nanos_of_day() - nanos)
+    }
+}
+
+impl AddAssign<Duration> for Clock {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+impl SubAssign<Duration> for Clock {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+/// The time from `other` forward to `self`, which is less than a day, so that
+/// `other + (self - other) == self`.
+impl Sub for Clock {
+    type Output = Duration;
+
+    fn sub(self, other: Clock) -> Duration {
+        let nanos = (self.nanos_of_day() - other.nanos_of_day()).rem_euclid(NANOS_PER_DAY);
+        Duration::from_nanos(nanos as u64)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseClockError {
+    /// There is nothing but whitespace.
+    Empty,
+    /// The hour is not a number from 0 to 23.
+    BadHour,
+    /// The minute is not a two-digit number from 00 to 59.
+    BadMinute,
+    /// The second is not a two-digit number from 00 to 59.
+    BadSecond,
+    /// The fraction of a second is not a number of up to nine digits, or
+    /// follows the minutes instead of the seconds.
+    BadFraction,
+    /// The UTC offset after the time is not `±hh`, `±hhmm` or `±hh:mm`, with
+    /// hours from 00 to 23 and minutes from 00 to 59.
+    BadOffset,
+    /// The parts of the time are not separated by colons, nor written together
+    /// as four or six digits.
+    MissingSeparator,
+    /// An AM or PM time has an hour which is not from 1 to 12.
+    AmPmOutOfRange,
+}
+
+impl fmt::Display for ParseClockError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:0>2}:{:0>2}", self.hours, self.minutes)
+        let message = match self {
+            ParseClockError::Empty => "no time given",
+            ParseClockError::BadHour => "hour must be from 0 to 23",
+            ParseClockError::BadMinute => "minute must be from 00 to 59",
+            ParseClockError::BadSecond => "second must be from 00 to 59",
+            ParseClockError::BadFraction => {
+                "fraction of a second must be up to nine digits after the seconds"
+            }
+            ParseClockError::BadOffset => "UTC offset must be ±hh, ±hhmm or ±hh:mm",
+            ParseClockError::MissingSeparator => "expected hours and minutes separated by ':'",
+            ParseClockError::AmPmOutOfRange => "hour must be from 1 to 12 with AM or PM",
+        };
+        write!(f, "{}", message)
     }
 }
 
-impl PartialEq for Clock {
-    fn eq(&self, other: &Self) -> bool {
-        self.hours == other.hours && self.minutes == other.minutes
+impl Error for ParseClockError {}
+
+impl FromStr for Clock {
+    type Err = ParseClockError;
+
+    /// Parse a time of day in 24-hour time, such as `20:30`, `20:30:15` or
+    /// `2030`, or in 12-hour time, such as `8:30 pm` or `8 PM`. ISO 8601 times
+    /// are accepted too, with an optional leading `T`, fractions of a second
+    /// such as `20:30:15.250`, and a trailing `Z` or UTC offset such as
+    /// `+02:00`. The offset is checked but not applied: the clock shows the
+    /// local time as written.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseClockError::Empty);
+        }
+        let lower = s.to_ascii_lowercase();
+        let (time, meridiem) = match strip_meridiem(&lower) {
+            Some((time, pm)) => (time, Some(pm)),
+            None => {
+                let time = lower.strip_prefix('t').unwrap_or(&lower);
+                let time = match time.find(['+', '-']) {
+                    Some(i) => {
+                        check_offset(&time[i + 1..])?;
+                        &time[..i]
+                    }
+                    None => time.strip_suffix('z').unwrap_or(time),
+                };
+                (time, None)
+            }
+        };
+        let (time, fraction) = match time.find(['.', ',']) {
+            Some(i) => (&time[..i], Some(&time[i + 1..])),
+            None => (time, None),
+        };
+
+        let parts: Vec<&str> = if time.contains(':') {
+            time.split(':').collect()
+        } else if time.bytes().all(|b| b.is_ascii_digit()) {
+            match time.len() {
+                // An hour on its own, as in "8 pm"
+                1 | 2 if meridiem.is_some() => vec![time, "00"],
+                4 => vec![&time[..2], &time[2..]],
+                6 => vec![&time[..2], &time[2..4], &time[4..]],
+                _ => return Err(ParseClockError::MissingSeparator),
+            }
+        } else {
+            return Err(ParseClockError::MissingSeparator);
+        };
+        let (hours, minutes, seconds) = match parts[..] {
+            [hours, minutes] => (hours, minutes, None),
+            [hours, minutes, seconds] => (hours, minutes, Some(seconds)),
+            _ => return Err(ParseClockError::MissingSeparator),
+        };
+
+        let hours = parse_digits(hours, 1..=2).ok_or(ParseClockError::BadHour)?;
+        let minutes = parse_digits(minutes, 2..=2)
+            .filter(|minutes| *minutes < 60)
+            .ok_or(ParseClockError::BadMinute)?;
+        // A fraction after the minutes would be a fraction of a minute in ISO
+        // 8601, so "20:30.5" is 20:30:30, which is not supported
+        if fraction.is_some() && seconds.is_none() {
+            return Err(ParseClockError::BadFraction);
+        }
+        let seconds = match seconds {
+            Some(seconds) => parse_digits(seconds, 2..=2)
+                .filter(|seconds| *seconds < 60)
+                .ok_or(ParseClockError::BadSecond)?,
+            None => 0,
+        };
+        let nanoseconds = match fraction {
+            // Pad to nine digits, so that ".25" is 250 000 000 nanoseconds
+            Some(fraction) => parse_digits(fraction, 1..=9)
+                .map(|digits| digits * 10u32.pow(9 - fraction.len() as u32))
+                .ok_or(ParseClockError::BadFraction)?,
+            None => 0,
+        };
+
+        let hours = match meridiem {
+            Some(pm) => {
+                if !(1..=12).contains(&hours) {
+                    return Err(ParseClockError::AmPmOutOfRange);
+                }
+                hours % 12 + if pm { 12 } else { 0 }
+            }
+            // ISO 8601 allows 24:00 for the midnight at the end of the day
+            None if hours == 24 && minutes == 0 && seconds == 0 && nanoseconds == 0 => 0,
+            None if hours < 24 => hours,
+            None => return Err(ParseClockError::BadHour),
+        };
+        Ok(Clock::with_nanoseconds(
+            hours as i32,
+            minutes as i32,
+            seconds as i32,
+            nanoseconds as i64,
+        ))
+    }
+}
+
+/// Split an "am" or "pm" suffix, with or without dots and a space before it,
+/// from a lower case time. The flag is true for "pm".
+fn strip_meridiem(s: &str) -> Option<(&str, bool)> {
+    for (suffix, pm) in [("a.m.", false), ("p.m.", true), ("am", false), ("pm", true)] {
+        if let Some(time) = s.strip_suffix(suffix) {
+            return Some((time.trim_end(), pm));
+        }
+    }
+    None
+}
+
+/// Check a UTC offset after its sign: `hh`, `hhmm` or `hh:mm`.
+fn check_offset(offset: &str) -> Result<(), ParseClockError> {
+    let (hours, minutes) = match offset.len() {
+        2 => (offset, "00"),
+        4 if offset.is_ascii() => offset.split_at(2),
+        5 if offset.as_bytes()[2] == b':' => (&offset[..2], &offset[3..]),
+        _ => return Err(ParseClockError::BadOffset),
+    };
+    match (parse_digits(hours, 2..=2), parse_digits(minutes, 2..=2)) {
+        (Some(hours), Some(minutes)) if hours < 24 && minutes < 60 => Ok(()),
+        _ => Err(ParseClockError::BadOffset),
+    }
+}
+
+/// Parse a number made of only ASCII digits, with a number of digits in `len`.
+fn parse_digits(s: &str, len: std::ops::RangeInclusive<usize>) -> Option<u32> {
+    if len.contains(&s.len()) && s.bytes().all(|b| b.is_ascii_digit()) {
+        s.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Formats as `HH:MM`, or as `HH:MM:SS` if there are seconds, or as
+/// `HH:MM:SS.fff` if there are fractions of a second, truncated to milliseconds.
+/// A fraction of less than a millisecond is widened to microseconds, or to
+/// nanoseconds if it is less than a microsecond, so that it never shows as zero.
+///
+/// A precision always shows the seconds with that many fractional digits, up
+/// to nine: `{:.0}` gives `HH:MM:SS` and `{:.3}` gives `HH:MM:SS.fff`.
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = match f.precision() {
+            Some(precision) => precision.min(9),
+            None if self.nanoseconds >= 1_000_000 => 3,
+            None if self.nanoseconds >= 1_000 => 6,
+            None if self.nanoseconds != 0 => 9,
+            None if self.seconds != 0 => 0,
+            None => return write!(f, "{:0>2}:{:0>2}", self.hours, self.minutes),
+        };
+        write!(
+            f,
+            "{:0>2}:{:0>2}:{:0>2}",
+            self.hours, self.minutes, self.seconds
+        )?;
+        if digits > 0 {
+            let fraction = self.nanoseconds / 10u32.pow(9 - digits as u32);
+            write!(f, ".{:0>width$}", fraction, width = digits)?;
+        }
+        Ok(())
     }
 }
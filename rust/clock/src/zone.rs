@@ -0,0 +1,512 @@
+//! Clocks at a UTC offset, and named time zones whose offset changes with
+//! daylight saving time, loaded from the `tzdata/zones` file bundled with the
+//! crate.
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use crate::{Clock, Date, Weekday, NANOS_PER_SECOND, SECONDS_PER_DAY};
+
+const BUNDLED_ZONES: &str = include_str!("../tzdata/zones");
+
+/// How far a time zone is ahead of UTC, or behind it if negative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UtcOffset {
+    seconds: i32,
+}
+
+impl UtcOffset {
+    pub const UTC: UtcOffset = UtcOffset { seconds: 0 };
+
+    /// The offset, or `None` if it is a whole day or more.
+    pub fn from_seconds(seconds: i32) -> Option<Self> {
+        if seconds.unsigned_abs() < SECONDS_PER_DAY as u32 {
+            Some(UtcOffset { seconds })
+        } else {
+            None
+        }
+    }
+
+    pub fn from_minutes(minutes: i32) -> Option<Self> {
+        UtcOffset::from_seconds(minutes.checked_mul(60)?)
+    }
+
+    pub fn from_hours(hours: i32) -> Option<Self> {
+        UtcOffset::from_seconds(hours.checked_mul(3600)?)
+    }
+
+    pub fn seconds(&self) -> i32 {
+        self.seconds
+    }
+}
+
+/// Formats as `+HH:MM`, or `+HH:MM:SS` if the offset is not whole minutes.
+impl fmt::Display for UtcOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.seconds < 0 { '-' } else { '+' };
+        let seconds = self.seconds.unsigned_abs();
+        write!(
+            f,
+            "{}{:0>2}:{:0>2}",
+            sign,
+            seconds / 3600,
+            seconds / 60 % 60
+        )?;
+        if !seconds.is_multiple_of(60) {
+            write!(f, ":{:0>2}", seconds % 60)?;
+        }
+        Ok(())
+    }
+}
+
+/// A clock at a fixed UTC offset.
+///
+/// Two zoned clocks are only equal if both the clock and the offset are, so
+/// `10:00+01:00` is not equal to `09:00+00:00`; compare `to_utc` for the same
+/// moment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ZonedClock {
+    clock: Clock,
+    offset: UtcOffset,
+}
+
+impl ZonedClock {
+    pub fn new(clock: Clock, offset: UtcOffset) -> Self {
+        ZonedClock { clock, offset }
+    }
+
+    pub fn utc(clock: Clock) -> Self {
+        ZonedClock::new(clock, UtcOffset::UTC)
+    }
+
+    pub fn clock(&self) -> Clock {
+        self.clock
+    }
+
+    pub fn offset(&self) -> UtcOffset {
+        self.offset
+    }
+
+    /// The same moment at another offset, with the day shift: 1 if the
+    /// moment is on the next day at `offset`, -1 if on the day before, or 0.
+    pub fn to_offset(&self, offset: UtcOffset) -> (ZonedClock, i32) {
+        let seconds = self.clock.nanos_of_day() / NANOS_PER_SECOND
+            + (offset.seconds - self.offset.seconds) as i64;
+        let day_shift = seconds.div_euclid(SECONDS_PER_DAY) as i32;
+        let clock = Clock::with_nanoseconds(0, 0, seconds as i32, self.clock.nanoseconds() as i64);
+        (ZonedClock::new(clock, offset), day_shift)
+    }
+
+    /// The same moment in UTC, with the day shift as for `to_offset`.
+    pub fn to_utc(&self) -> (Clock, i32) {
+        let (utc, day_shift) = self.to_offset(UtcOffset::UTC);
+        (utc.clock, day_shift)
+    }
+}
+
+/// Formats as the clock followed by the offset, as in `08:30+01:00`.
+impl fmt::Display for ZonedClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.clock, f)?;
+        write!(f, "{}", self.offset)
+    }
+}
+
+/// A named time zone, such as `Europe/Oslo`, with a standard offset and
+/// possibly a yearly rule for daylight saving time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeZone {
+    name: String,
+    standard: Period,
+    daylight: Option<Daylight>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Period {
+    abbreviation: String,
+    offset: UtcOffset,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Daylight {
+    period: Period,
+    start: Rule,
+    end: Rule,
+}
+
+/// A change of offset on a day of the year, as in `M3.5.0/2`: on the fifth,
+/// meaning last, Sunday of March at 02:00 local time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    month: u32,
+    week: u32,
+    weekday: Weekday,
+    // Seconds after local midnight, which may be negative or more than a day
+    time: i64,
+}
+
+/// A change of offset in a time zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    /// The local date on which the offset changes, by the offset before it.
+    pub date: Date,
+    /// The local time at which the offset changes, by the offset before it.
+    pub clock: Clock,
+    pub from: UtcOffset,
+    pub to: UtcOffset,
+}
+
+impl TimeZone {
+    /// A time zone from a POSIX TZ string such as `CET-1CEST,M3.5.0,M10.5.0/3`.
+    /// Note that the offsets in these strings are west of UTC, so `CET-1` is
+    /// one hour ahead of UTC.
+    ///
+    /// Only daylight saving rules in the `Mm.w.d` form are supported.
+    pub fn from_posix(name: &str, rule: &str) -> Result<Self, ParseZoneErrorKind> {
+        let mut parser = PosixParser { rest: rule };
+        let standard = parser.period(None)?;
+        let daylight = if parser.rest.is_empty() {
+            None
+        } else {
+            // Daylight saving time is an hour ahead of standard time unless
+            // the offset is given
+            let period = parser.period(Some(UtcOffset {
+                seconds: standard.offset.seconds + 3600,
+            }))?;
+            if !parser.eat(',') {
+                return Err(ParseZoneErrorKind::MissingRule);
+            }
+            let start = parser.rule()?;
+            if !parser.eat(',') {
+                return Err(ParseZoneErrorKind::MissingRule);
+            }
+            let end = parser.rule()?;
+            if !parser.rest.is_empty() {
+                return Err(ParseZoneErrorKind::TrailingInput(parser.rest.to_string()));
+            }
+            Some(Daylight { period, start, end })
+        };
+        Ok(TimeZone {
+            name: name.to_string(),
+            standard,
+            daylight,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn standard_offset(&self) -> UtcOffset {
+        self.standard.offset
+    }
+
+    pub fn has_daylight_saving(&self) -> bool {
+        self.daylight.is_some()
+    }
+
+    /// The offset in effect at `utc` on the UTC date `date`.
+    pub fn offset_at(&self, date: Date, utc: Clock) -> UtcOffset {
+        self.period_at(date, utc).offset
+    }
+
+    /// The abbreviation in effect at `utc` on the UTC date `date`, such as
+    /// `CEST`, or a number such as `+0545` for zones without one.
+    pub fn abbreviation_at(&self, date: Date, utc: Clock) -> &str {
+        &self.period_at(date, utc).abbreviation
+    }
+
+    /// The local date and clock in this zone at `utc` on the UTC date `date`.
+    pub fn to_local(&self, date: Date, utc: Clock) -> (Date, ZonedClock) {
+        let offset = self.offset_at(date, utc);
+        let (local, day_shift) = ZonedClock::utc(utc).to_offset(offset);
+        (date.add_days(day_shift as i64), local)
+    }
+
+    /// The changes of offset in `year`, in the order they happen.
+    pub fn transitions(&self, year: i32) -> Vec<Transition> {
+        let daylight = match &self.daylight {
+            Some(daylight) => daylight,
+            None => return Vec::new(),
+        };
+        let start = Transition::new(&daylight.start, year, &self.standard, &daylight.period);
+        let end = Transition::new(&daylight.end, year, &daylight.period, &self.standard);
+        if (start.date, start.clock) < (end.date, end.clock) {
+            vec![start, end]
+        } else {
+            vec![end, start]
+        }
+    }
+
+    /// The change of offset on the local date `date`, if there is one.
+    pub fn transition_on(&self, date: Date) -> Option<Transition> {
+        self.transitions(date.year())
+            .into_iter()
+            .find(|transition| transition.date == date)
+    }
+
+    fn period_at(&self, date: Date, utc: Clock) -> &Period {
+        let daylight = match &self.daylight {
+            Some(daylight) => daylight,
+            None => return &self.standard,
+        };
+        let instant =
+            date.days_since_epoch() * SECONDS_PER_DAY + utc.nanos_of_day() / NANOS_PER_SECOND;
+        // Daylight saving time starts by the clock of standard time, and ends
+        // by its own clock
+        let start = daylight.start.local_seconds(date.year()) - self.standard.offset.seconds as i64;
+        let end = daylight.end.local_seconds(date.year()) - daylight.period.offset.seconds as i64;
+        let in_daylight = if start < end {
+            start <= instant && instant < end
+        } else {
+            // In the southern hemisphere, daylight saving time spans the new year
+            instant < end || start <= instant
+        };
+        if in_daylight {
+            &daylight.period
+        } else {
+            &self.standard
+        }
+    }
+}
+
+impl Rule {
+    /// The local date of the rule in `year`.
+    fn date(&self, year: i32) -> Date {
+        let first = Date::new(year, self.month, 1).unwrap();
+        let offset = (self.weekday.index() + 7 - first.weekday().index()) % 7;
+        let mut date = first.add_days(offset as i64 + 7 * (self.week as i64 - 1));
+        // The fifth week means the last, which may be the fourth
+        while date.month() != self.month {
+            date = date.add_days(-7);
+        }
+        date
+    }
+
+    /// Seconds since the epoch of the rule in `year`, counted by local time.
+    fn local_seconds(&self, year: i32) -> i64 {
+        self.date(year).days_since_epoch() * SECONDS_PER_DAY + self.time
+    }
+}
+
+impl Transition {
+    fn new(rule: &Rule, year: i32, from: &Period, to: &Period) -> Self {
+        let seconds = rule.local_seconds(year);
+        Transition {
+            date: Date::from_days_since_epoch(seconds.div_euclid(SECONDS_PER_DAY)),
+            clock: Clock::with_seconds(0, 0, seconds.rem_euclid(SECONDS_PER_DAY) as i32),
+            from: from.offset,
+            to: to.offset,
+        }
+    }
+}
+
+/// A reader for the parts of a POSIX TZ string.
+struct PosixParser<'a> {
+    rest: &'a str,
+}
+
+impl PosixParser<'_> {
+    fn eat(&mut self, c: char) -> bool {
+        match self.rest.strip_prefix(c) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &str {
+        let end = self.rest.find(|c| !predicate(c)).unwrap_or(self.rest.len());
+        let (taken, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        taken
+    }
+
+    /// An abbreviation and offset, such as `CET-1` or `<+0545>-5:45`. The
+    /// offset may be left out if there is a `default`.
+    fn period(&mut self, default: Option<UtcOffset>) -> Result<Period, ParseZoneErrorKind> {
+        let abbreviation = if self.eat('<') {
+            let abbreviation = self.take_while(|c| c != '>').to_string();
+            if !self.eat('>') {
+                return Err(ParseZoneErrorKind::BadAbbreviation);
+            }
+            abbreviation
+        } else {
+            self.take_while(|c| c.is_ascii_alphabetic()).to_string()
+        };
+        if abbreviation.len() < 3 {
+            return Err(ParseZoneErrorKind::BadAbbreviation);
+        }
+        let offset = match default {
+            Some(offset) if self.rest.is_empty() || self.rest.starts_with(',') => offset,
+            _ => {
+                let seconds = self.seconds(24).ok_or(ParseZoneErrorKind::BadOffset)?;
+                // West of UTC is positive in POSIX, the other way to 'UtcOffset'
+                UtcOffset::from_seconds(-seconds as i32).ok_or(ParseZoneErrorKind::BadOffset)?
+            }
+        };
+        Ok(Period {
+            abbreviation,
+            offset,
+        })
+    }
+
+    /// A rule such as `M3.5.0` or `M10.5.0/3`.
+    fn rule(&mut self) -> Result<Rule, ParseZoneErrorKind> {
+        if !self.eat('M') {
+            return Err(ParseZoneErrorKind::UnsupportedRule);
+        }
+        let month = self.number(1..=12)?;
+        if !self.eat('.') {
+            return Err(ParseZoneErrorKind::BadRule);
+        }
+        let week = self.number(1..=5)?;
+        if !self.eat('.') {
+            return Err(ParseZoneErrorKind::BadRule);
+        }
+        // POSIX counts the days of the week from Sunday
+        let weekday = Weekday::from_index(self.number(0..=6)? as usize + 6);
+        let time = if self.eat('/') {
+            self.seconds(167).ok_or(ParseZoneErrorKind::BadRule)?
+        } else {
+            2 * 3600
+        };
+        Ok(Rule {
+            month,
+            week,
+            weekday,
+            time,
+        })
+    }
+
+    /// A number in a rule, which must be in `range`.
+    fn number(&mut self, range: RangeInclusive<u32>) -> Result<u32, ParseZoneErrorKind> {
+        self.take_while(|c| c.is_ascii_digit())
+            .parse()
+            .ok()
+            .filter(|n| range.contains(n))
+            .ok_or(ParseZoneErrorKind::BadRule)
+    }
+
+    /// A signed `hh[:mm[:ss]]`, in seconds.
+    fn seconds(&mut self, max_hours: i64) -> Option<i64> {
+        let sign = if self.eat('-') {
+            -1
+        } else {
+            self.eat('+');
+            1
+        };
+        let hours: i64 = self.take_while(|c| c.is_ascii_digit()).parse().ok()?;
+        if hours > max_hours {
+            return None;
+        }
+        let mut seconds = hours * 3600;
+        for unit in [60, 1] {
+            if !self.eat(':') {
+                break;
+            }
+            let digits = self.take_while(|c| c.is_ascii_digit());
+            let value: i64 = digits.parse().ok().filter(|value| *value < 60)?;
+            if digits.len() != 2 {
+                return None;
+            }
+            seconds += value * unit;
+        }
+        Some(sign * seconds)
+    }
+}
+
+/// The time zones of a tzdata file, by name.
+///
+/// The file has one zone per line: the name, then whitespace, then the rule as
+/// a POSIX TZ string. Blank lines and lines starting with `#` are skipped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TzData {
+    zones: BTreeMap<String, TimeZone>,
+}
+
+impl TzData {
+    /// The zones bundled with the crate.
+    pub fn bundled() -> Self {
+        BUNDLED_ZONES
+            .parse()
+            .expect("the bundled tzdata file is valid")
+    }
+
+    pub fn zone(&self, name: &str) -> Option<&TimeZone> {
+        self.zones.get(name)
+    }
+
+    /// The names of the zones, in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.zones.keys().map(String::as_str)
+    }
+}
+
+impl FromStr for TzData {
+    type Err = ParseZoneError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut zones = BTreeMap::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |kind| ParseZoneError { line: i + 1, kind };
+            let (name, rule) = line
+                .split_once(char::is_whitespace)
+                .ok_or(error(ParseZoneErrorKind::MissingRule))?;
+            let zone = TimeZone::from_posix(name, rule.trim()).map_err(error)?;
+            if zones.insert(name.to_string(), zone).is_some() {
+                return Err(error(ParseZoneErrorKind::DuplicateZone(name.to_string())));
+            }
+        }
+        Ok(TzData { zones })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseZoneErrorKind {
+    /// A zone has no rule, or daylight saving time has no start or end.
+    MissingRule,
+    /// An abbreviation is shorter than three letters, or a `<` is not closed.
+    BadAbbreviation,
+    /// An offset is not `[+-]hh[:mm[:ss]]` of less than a day.
+    BadOffset,
+    /// A daylight saving rule is not `Mm.w.d[/time]`.
+    BadRule,
+    /// A daylight saving rule is in the `Jn` or `n` form.
+    UnsupportedRule,
+    TrailingInput(String),
+    DuplicateZone(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseZoneError {
+    pub line: usize,
+    pub kind: ParseZoneErrorKind,
+}
+
+impl fmt::Display for ParseZoneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ParseZoneErrorKind::MissingRule => write!(f, "missing rule"),
+            ParseZoneErrorKind::BadAbbreviation => write!(f, "bad abbreviation"),
+            ParseZoneErrorKind::BadOffset => write!(f, "bad offset"),
+            ParseZoneErrorKind::BadRule => write!(f, "bad daylight saving rule"),
+            ParseZoneErrorKind::UnsupportedRule => {
+                write!(f, "only Mm.w.d daylight saving rules are supported")
+            }
+            ParseZoneErrorKind::TrailingInput(rest) => write!(f, "unexpected '{}'", rest),
+            ParseZoneErrorKind::DuplicateZone(name) => write!(f, "zone {} given twice", name),
+        }
+    }
+}
+
+impl Error for ParseZoneError {}
@@ -1,4 +1,11 @@
-use clock::Clock;
+use clock::{
+    Clock, ClockInterval, Date, IntervalSet, ManualTimeSource, Occurrence, ParseClockError,
+    ParseZoneError, ParseZoneErrorKind, Scheduler, SystemTimeSource, TimeSource, Transition,
+    TzData, UtcOffset, Weekday, WeekdayMask, ZonedClock,
+};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 //
 // Clock Creation
@@ -326,3 +333,949 @@ fn test_compare_clocks_with_negative_hours_and_minutes_that_wrap() {
 fn test_compare_full_clock_and_zeroed_clock() {
     assert_eq!(Clock::new(24, 0), Clock::new(0, 0));
 }
+
+//
+// Seconds and Fractions of a Second
+//
+
+#[test]
+fn test_seconds_are_shown_when_present() {
+    assert_eq!(Clock::with_seconds(8, 30, 15).to_string(), "08:30:15");
+    assert_eq!(Clock::with_seconds(8, 30, 0).to_string(), "08:30");
+}
+
+#[test]
+fn test_fractions_are_shown_as_milliseconds() {
+    assert_eq!(
+        Clock::with_nanoseconds(8, 30, 0, 123_456_789).to_string(),
+        "08:30:00.123"
+    );
+}
+
+#[test]
+fn test_precision_chooses_the_fractional_digits() {
+    let clock = Clock::with_nanoseconds(8, 30, 15, 123_456_789);
+    assert_eq!(format!("{:.0}", clock), "08:30:15");
+    assert_eq!(format!("{:.6}", clock), "08:30:15.123456");
+    assert_eq!(format!("{:.0}", Clock::new(8, 30)), "08:30:00");
+}
+
+#[test]
+fn test_fractions_below_a_millisecond_are_not_shown_as_zero() {
+    assert_eq!(
+        Clock::with_nanoseconds(0, 0, 0, 500).to_string(),
+        "00:00:00.000000500"
+    );
+    assert_eq!(
+        Clock::with_nanoseconds(0, 0, 0, 123_456).to_string(),
+        "00:00:00.000123"
+    );
+    assert_eq!(
+        Clock::with_nanoseconds(0, 0, 0, 1_000_001).to_string(),
+        "00:00:00.001"
+    );
+}
+
+#[test]
+fn test_add_seconds_does_not_overflow() {
+    // i32::MAX seconds is 24855 days, 3 hours, 14 minutes and 7 seconds
+    assert_eq!(
+        Clock::with_seconds(0, 0, 30).add_seconds(i32::MAX),
+        Clock::with_seconds(3, 14, 37)
+    );
+    assert_eq!(
+        Clock::with_seconds(0, 0, -30).add_seconds(i32::MIN),
+        Clock::with_seconds(20, 45, 22)
+    );
+}
+
+#[test]
+fn test_seconds_roll_over() {
+    assert_eq!(
+        Clock::with_seconds(23, 59, 75),
+        Clock::with_seconds(0, 0, 15)
+    );
+    assert_eq!(
+        Clock::with_seconds(0, 0, -1),
+        Clock::with_seconds(23, 59, 59)
+    );
+}
+
+#[test]
+fn test_negative_nanoseconds_borrow_a_second() {
+    let clock = Clock::with_nanoseconds(0, 0, 0, -1);
+    assert_eq!(clock.seconds(), 59);
+    assert_eq!(clock.nanoseconds(), 999_999_999);
+    assert_eq!(clock.hours(), 23);
+}
+
+#[test]
+fn test_add_seconds() {
+    assert_eq!(Clock::new(10, 0).add_seconds(3_661).to_string(), "11:01:01");
+    assert_eq!(Clock::new(0, 0).add_seconds(-1).to_string(), "23:59:59");
+}
+
+#[test]
+fn test_add_minutes_keeps_seconds() {
+    assert_eq!(
+        Clock::with_seconds(10, 0, 30).add_minutes(5).to_string(),
+        "10:05:30"
+    );
+}
+
+#[test]
+fn test_add_duration() {
+    let clock = Clock::with_nanoseconds(23, 59, 59, 900_000_000);
+    assert_eq!(
+        clock.add_duration(Duration::from_millis(250)).to_string(),
+        "00:00:00.150"
+    );
+    assert_eq!(
+        Clock::new(6, 0).add_duration(Duration::from_secs(3 * 86_400 + 60)),
+        Clock::new(6, 1)
+    );
+}
+
+//
+// Parsing and 12-hour Time
+//
+
+#[test]
+fn test_parse_24_hour_times() {
+    assert_eq!("08:30".parse(), Ok(Clock::new(8, 30)));
+    assert_eq!("8:30".parse(), Ok(Clock::new(8, 30)));
+    assert_eq!("20:30:15".parse(), Ok(Clock::with_seconds(20, 30, 15)));
+    assert_eq!("0830".parse(), Ok(Clock::new(8, 30)));
+    assert_eq!("203015".parse(), Ok(Clock::with_seconds(20, 30, 15)));
+}
+
+#[test]
+fn test_parse_12_hour_times() {
+    assert_eq!("8:30 pm".parse(), Ok(Clock::new(20, 30)));
+    assert_eq!("8:30PM".parse(), Ok(Clock::new(20, 30)));
+    assert_eq!("8 a.m.".parse(), Ok(Clock::new(8, 0)));
+    assert_eq!("12:15 am".parse(), Ok(Clock::new(0, 15)));
+    assert_eq!("12:15 pm".parse(), Ok(Clock::new(12, 15)));
+}
+
+#[test]
+fn test_parse_iso_8601_times() {
+    assert_eq!(
+        "T20:30:15.25Z".parse(),
+        Ok(Clock::with_nanoseconds(20, 30, 15, 250_000_000))
+    );
+    assert_eq!(
+        "20:30:15,123456789".parse(),
+        Ok(Clock::with_nanoseconds(20, 30, 15, 123_456_789))
+    );
+    assert_eq!("24:00:00".parse(), Ok(Clock::new(0, 0)));
+}
+
+#[test]
+fn test_parse_iso_8601_times_with_offsets() {
+    // The offset is checked, and the clock keeps the local time as written
+    assert_eq!(
+        "20:30:15+02:00".parse(),
+        Ok(Clock::with_seconds(20, 30, 15))
+    );
+    assert_eq!(
+        "T20:30:15-05:00".parse(),
+        Ok(Clock::with_seconds(20, 30, 15))
+    );
+    assert_eq!(
+        "203015.5+0530".parse(),
+        Ok(Clock::with_nanoseconds(20, 30, 15, 500_000_000))
+    );
+    assert_eq!("20:30-08".parse(), Ok(Clock::new(20, 30)));
+    for bad in [
+        "20:30+2:00",
+        "20:30+25:00",
+        "20:30+02:60",
+        "20:30+",
+        "20:30-02:00Z",
+    ] {
+        assert_eq!(
+            bad.parse::<Clock>(),
+            Err(ParseClockError::BadOffset),
+            "{}",
+            bad
+        );
+    }
+}
+
+#[test]
+fn test_parse_fraction_needs_seconds() {
+    // In ISO 8601 "20:30.5" would be a fraction of a minute
+    assert_eq!(
+        "20:30.5".parse::<Clock>(),
+        Err(ParseClockError::BadFraction)
+    );
+    assert_eq!("2030.5".parse::<Clock>(), Err(ParseClockError::BadFraction));
+}
+
+#[test]
+fn test_parse_errors_name_the_problem() {
+    assert_eq!("".parse::<Clock>(), Err(ParseClockError::Empty));
+    assert_eq!("25:00".parse::<Clock>(), Err(ParseClockError::BadHour));
+    assert_eq!("24:01".parse::<Clock>(), Err(ParseClockError::BadHour));
+    assert_eq!("08:60".parse::<Clock>(), Err(ParseClockError::BadMinute));
+    assert_eq!("08:5".parse::<Clock>(), Err(ParseClockError::BadMinute));
+    assert_eq!("08:30:61".parse::<Clock>(), Err(ParseClockError::BadSecond));
+    assert_eq!(
+        "08:30:00.".parse::<Clock>(),
+        Err(ParseClockError::BadFraction)
+    );
+    assert_eq!(
+        "830".parse::<Clock>(),
+        Err(ParseClockError::MissingSeparator)
+    );
+    assert_eq!(
+        "8.30".parse::<Clock>(),
+        Err(ParseClockError::MissingSeparator)
+    );
+    assert_eq!(
+        "13:00 pm".parse::<Clock>(),
+        Err(ParseClockError::AmPmOutOfRange)
+    );
+    assert_eq!(
+        "0:30 am".parse::<Clock>(),
+        Err(ParseClockError::AmPmOutOfRange)
+    );
+}
+
+#[test]
+fn test_format_12_hour_time() {
+    assert_eq!(Clock::new(20, 30).to_12_hour_string(), "8:30 PM");
+    assert_eq!(Clock::new(0, 5).to_12_hour_string(), "12:05 AM");
+    assert_eq!(Clock::new(12, 0).to_12_hour_string(), "12:00 PM");
+    assert_eq!(
+        Clock::with_seconds(9, 0, 7).to_12_hour_string(),
+        "9:00:07 AM"
+    );
+}
+
+#[test]
+fn test_12_hour_time_round_trips() {
+    for minutes in (0..24 * 60).step_by(7) {
+        let clock = Clock::new(0, minutes);
+        assert_eq!(clock.to_12_hour_string().parse(), Ok(clock));
+    }
+}
+
+//
+// Operators, Ordering and Day Carry
+//
+
+#[test]
+fn test_add_and_subtract_durations() {
+    let clock = Clock::new(23, 30);
+    assert_eq!(clock + Duration::from_secs(45 * 60), Clock::new(0, 15));
+    assert_eq!(
+        clock - Duration::from_secs(24 * 3600 + 60),
+        Clock::new(23, 29)
+    );
+    assert_eq!(
+        Clock::new(0, 0) - Duration::from_nanos(1),
+        Clock::with_nanoseconds(23, 59, 59, 999_999_999)
+    );
+}
+
+#[test]
+fn test_add_and_subtract_assign() {
+    let mut clock = Clock::new(10, 0);
+    clock += Duration::from_secs(90);
+    assert_eq!(clock, Clock::with_seconds(10, 1, 30));
+    clock -= Duration::from_secs(3 * 3600);
+    assert_eq!(clock, Clock::with_seconds(7, 1, 30));
+}
+
+#[test]
+fn test_difference_is_forward_around_the_day() {
+    assert_eq!(
+        Clock::new(10, 0) - Clock::new(8, 30),
+        Duration::from_secs(90 * 60)
+    );
+    assert_eq!(
+        Clock::new(1, 0) - Clock::new(23, 0),
+        Duration::from_secs(2 * 3600)
+    );
+    assert_eq!(Clock::new(5, 0) - Clock::new(5, 0), Duration::ZERO);
+}
+
+#[test]
+fn test_adding_the_difference_gives_the_later_clock() {
+    let (a, b) = (Clock::with_nanoseconds(3, 4, 5, 6), Clock::new(22, 10));
+    assert_eq!(b + (a - b), a);
+    assert_eq!(a + (b - a), b);
+}
+
+#[test]
+fn test_clocks_are_ordered_through_the_day() {
+    let mut clocks = vec![
+        Clock::new(12, 0),
+        Clock::new(0, 1),
+        Clock::with_seconds(0, 0, 59),
+    ];
+    clocks.sort();
+    assert_eq!(
+        clocks,
+        [
+            Clock::with_seconds(0, 0, 59),
+            Clock::new(0, 1),
+            Clock::new(12, 0)
+        ]
+    );
+    assert!(Clock::new(23, 59) > Clock::new(-1, 0).add_minutes(58));
+}
+
+#[test]
+fn test_clocks_as_map_keys_and_set_members() {
+    let mut schedule = BTreeMap::new();
+    schedule.insert(Clock::new(17, 0), "home");
+    schedule.insert(Clock::new(9, 0), "work");
+    assert_eq!(schedule.values().collect::<Vec<_>>(), [&"work", &"home"]);
+
+    let set: HashSet<Clock> = [Clock::new(24, 0), Clock::new(0, 0), Clock::new(1, -60)]
+        .into_iter()
+        .collect();
+    assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn test_add_minutes_reports_midnights_crossed() {
+    assert_eq!(
+        Clock::new(10, 0).add_minutes_with_carry(60),
+        (Clock::new(11, 0), 0)
+    );
+    assert_eq!(
+        Clock::new(23, 0).add_minutes_with_carry(60),
+        (Clock::new(0, 0), 1)
+    );
+    assert_eq!(
+        Clock::new(23, 0).add_minutes_with_carry(3 * 24 * 60 + 120),
+        (Clock::new(1, 0), 4)
+    );
+    assert_eq!(
+        Clock::new(0, 30).add_minutes_with_carry(-31),
+        (Clock::new(23, 59), -1)
+    );
+}
+
+//
+// Intervals
+//
+
+fn interval(start: (i32, i32), end: (i32, i32)) -> ClockInterval {
+    ClockInterval::new(Clock::new(start.0, start.1), Clock::new(end.0, end.1))
+}
+
+fn hours(hours: u64) -> Duration {
+    Duration::from_secs(hours * 3600)
+}
+
+#[test]
+fn test_interval_contains_start_but_not_end() {
+    let work = interval((9, 0), (17, 30));
+    assert!(work.contains(Clock::new(9, 0)));
+    assert!(work.contains(Clock::with_seconds(17, 29, 59)));
+    assert!(!work.contains(Clock::new(17, 30)));
+    assert!(!work.contains(Clock::new(8, 59)));
+    assert_eq!(work.duration(), Duration::from_secs(8 * 3600 + 30 * 60));
+    assert!(!work.wraps_midnight());
+}
+
+#[test]
+fn test_interval_wrapping_past_midnight() {
+    let night = interval((22, 0), (6, 0));
+    assert!(night.wraps_midnight());
+    assert_eq!(night.duration(), hours(8));
+    assert_eq!(night.end(), Clock::new(6, 0));
+    assert!(night.contains(Clock::new(23, 0)));
+    assert!(night.contains(Clock::new(0, 0)));
+    assert!(night.contains(Clock::new(5, 59)));
+    assert!(!night.contains(Clock::new(6, 0)));
+    assert!(!night.contains(Clock::new(12, 0)));
+}
+
+#[test]
+fn test_empty_and_whole_day_intervals() {
+    let empty = interval((8, 0), (8, 0));
+    assert!(empty.is_empty());
+    assert!(!empty.contains(Clock::new(8, 0)));
+
+    let day = ClockInterval::with_duration(Clock::new(8, 0), hours(30));
+    assert!(day.is_whole_day());
+    assert!(day.contains(Clock::new(7, 59)));
+    assert!(ClockInterval::whole_day().contains(Clock::new(23, 59)));
+}
+
+#[test]
+fn test_overlapping_intervals() {
+    let night = interval((22, 0), (6, 0));
+    assert!(night.overlaps(&interval((5, 0), (7, 0))));
+    assert!(night.overlaps(&interval((21, 0), (23, 0))));
+    assert!(!night.overlaps(&interval((6, 0), (22, 0))));
+    assert!(!interval((9, 0), (12, 0)).overlaps(&interval((12, 0), (13, 0))));
+}
+
+#[test]
+fn test_intersection_of_intervals() {
+    let work = interval((9, 0), (17, 30));
+    assert_eq!(
+        work.intersection(&interval((12, 0), (20, 0)))
+            .iter()
+            .collect::<Vec<_>>(),
+        [interval((12, 0), (17, 30))]
+    );
+    assert!(work.intersection(&interval((18, 0), (8, 0))).is_empty());
+}
+
+#[test]
+fn test_intersection_of_wrapping_intervals_can_be_two_spans() {
+    let night = interval((22, 0), (6, 0));
+    let long_day = interval((4, 0), (23, 0));
+    let both = night.intersection(&long_day);
+    assert_eq!(
+        both.iter().collect::<Vec<_>>(),
+        [interval((4, 0), (6, 0)), interval((22, 0), (23, 0))]
+    );
+    assert_eq!(both.duration(), hours(3));
+}
+
+#[test]
+fn test_union_merges_overlapping_and_touching_intervals() {
+    let set: IntervalSet = [
+        interval((13, 0), (15, 0)),
+        interval((9, 0), (11, 0)),
+        interval((10, 0), (12, 0)),
+        interval((12, 0), (12, 30)),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(
+        set.iter().collect::<Vec<_>>(),
+        [interval((9, 0), (12, 30)), interval((13, 0), (15, 0))]
+    );
+    assert_eq!(set.duration(), Duration::from_secs(5 * 3600 + 30 * 60));
+}
+
+#[test]
+fn test_union_keeps_intervals_across_midnight_together() {
+    let set = interval((23, 0), (1, 0)).union(&interval((0, 30), (2, 0)));
+    assert_eq!(set.iter().collect::<Vec<_>>(), [interval((23, 0), (2, 0))]);
+    assert!(set.contains(Clock::new(23, 30)));
+    assert!(set.contains(Clock::new(1, 30)));
+}
+
+#[test]
+fn test_union_of_interval_sets() {
+    let mornings = IntervalSet::from(interval((8, 0), (10, 0)));
+    let evenings: IntervalSet = [interval((18, 0), (20, 0)), interval((9, 0), (9, 30))]
+        .into_iter()
+        .collect();
+    let union = mornings.union(&evenings);
+    assert_eq!(
+        union.iter().collect::<Vec<_>>(),
+        [interval((8, 0), (10, 0)), interval((18, 0), (20, 0))]
+    );
+    assert_eq!(union.duration(), hours(4));
+}
+
+#[test]
+fn test_union_covering_the_day() {
+    let set = interval((6, 0), (22, 0)).union(&interval((22, 0), (6, 0)));
+    assert_eq!(set.duration(), hours(24));
+    assert_eq!(set.iter().count(), 1);
+    assert!(set.iter().all(|interval| interval.is_whole_day()));
+    assert!(set.free_slots(Duration::ZERO).is_empty());
+}
+
+#[test]
+fn test_free_slots_in_a_day() {
+    let busy: IntervalSet = [
+        interval((9, 0), (12, 0)),
+        interval((12, 15), (17, 0)),
+        interval((23, 0), (7, 0)),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(
+        busy.free_slots(Duration::ZERO),
+        [
+            interval((7, 0), (9, 0)),
+            interval((12, 0), (12, 15)),
+            interval((17, 0), (23, 0)),
+        ]
+    );
+    assert_eq!(
+        busy.free_slots(Duration::from_secs(30 * 60)),
+        [interval((7, 0), (9, 0)), interval((17, 0), (23, 0))]
+    );
+}
+
+#[test]
+fn test_free_slot_across_midnight() {
+    let busy = IntervalSet::from(interval((6, 0), (22, 0)));
+    assert_eq!(busy.free_slots(hours(8)), [interval((22, 0), (6, 0))]);
+    assert!(busy.free_slots(hours(9)).is_empty());
+    assert_eq!(
+        IntervalSet::new().free_slots(hours(24)),
+        [ClockInterval::whole_day()]
+    );
+}
+
+//
+// Dates, UTC Offsets and Time Zones
+//
+
+fn date(year: i32, month: u32, day: u32) -> Date {
+    Date::new(year, month, day).unwrap()
+}
+
+fn offset(hours: i32, minutes: i32) -> UtcOffset {
+    UtcOffset::from_minutes(hours * 60 + minutes).unwrap()
+}
+
+#[test]
+fn test_dates_and_weekdays() {
+    assert_eq!(date(2026, 10, 19).weekday(), Weekday::Monday);
+    assert_eq!(date(2000, 2, 29).weekday(), Weekday::Tuesday);
+    assert_eq!(date(1970, 1, 1).weekday(), Weekday::Thursday);
+    assert_eq!(Date::new(2026, 2, 29), None);
+    assert_eq!(Date::new(2026, 13, 1), None);
+    assert_eq!(date(2026, 12, 31).add_days(1), date(2027, 1, 1));
+    assert_eq!(date(2024, 3, 1).add_days(-1), date(2024, 2, 29));
+    assert_eq!(date(2026, 3, 8).to_string(), "2026-03-08");
+}
+
+#[test]
+fn test_utc_offsets() {
+    assert_eq!(offset(1, 0).to_string(), "+01:00");
+    assert_eq!(offset(-3, -30).to_string(), "-03:30");
+    assert_eq!(offset(5, 45).to_string(), "+05:45");
+    assert_eq!(UtcOffset::UTC.to_string(), "+00:00");
+    assert_eq!(UtcOffset::from_hours(24), None);
+    assert_eq!(
+        UtcOffset::from_seconds(-86_399).map(|o| o.seconds()),
+        Some(-86_399)
+    );
+}
+
+#[test]
+fn test_zoned_clock_converts_between_offsets() {
+    let oslo = ZonedClock::new(Clock::new(1, 30), offset(1, 0));
+    let (new_york, day_shift) = oslo.to_offset(offset(-5, 0));
+    assert_eq!(new_york.clock(), Clock::new(19, 30));
+    assert_eq!(day_shift, -1);
+    assert_eq!(new_york.to_string(), "19:30-05:00");
+
+    let (back, day_shift) = new_york.to_offset(offset(1, 0));
+    assert_eq!((back, day_shift), (oslo, 1));
+    assert_eq!(oslo.to_utc(), (Clock::new(0, 30), 0));
+}
+
+#[test]
+fn test_zoned_clock_keeps_fractions_of_a_second() {
+    let clock = ZonedClock::utc(Clock::with_nanoseconds(23, 59, 59, 500_000_000));
+    let (kolkata, day_shift) = clock.to_offset(offset(5, 30));
+    assert_eq!(
+        kolkata.clock(),
+        Clock::with_nanoseconds(5, 29, 59, 500_000_000)
+    );
+    assert_eq!(day_shift, 1);
+}
+
+#[test]
+fn test_bundled_zones() {
+    let tzdata = TzData::bundled();
+    assert!(tzdata.names().any(|name| name == "Europe/Oslo"));
+    assert!(tzdata
+        .zone("America/New_York")
+        .unwrap()
+        .has_daylight_saving());
+    assert!(!tzdata.zone("Asia/Tokyo").unwrap().has_daylight_saving());
+    assert_eq!(tzdata.zone("Mars/Olympus_Mons"), None);
+}
+
+#[test]
+fn test_zone_offset_follows_daylight_saving_time() {
+    let oslo = TzData::bundled().zone("Europe/Oslo").unwrap().clone();
+    let noon = Clock::new(12, 0);
+    assert_eq!(oslo.offset_at(date(2026, 1, 15), noon), offset(1, 0));
+    assert_eq!(oslo.abbreviation_at(date(2026, 1, 15), noon), "CET");
+    assert_eq!(oslo.offset_at(date(2026, 7, 15), noon), offset(2, 0));
+    assert_eq!(oslo.abbreviation_at(date(2026, 7, 15), noon), "CEST");
+}
+
+#[test]
+fn test_zone_offset_changes_at_the_moment_of_transition() {
+    let tzdata = TzData::bundled();
+    let oslo = tzdata.zone("Europe/Oslo").unwrap();
+    // Clocks go forward at 02:00 local time, which is 01:00 UTC
+    assert_eq!(
+        oslo.offset_at(date(2026, 3, 29), Clock::new(0, 59)),
+        offset(1, 0)
+    );
+    assert_eq!(
+        oslo.offset_at(date(2026, 3, 29), Clock::new(1, 0)),
+        offset(2, 0)
+    );
+    // And back at 03:00 local summer time, which is also 01:00 UTC
+    assert_eq!(
+        oslo.offset_at(date(2026, 10, 25), Clock::new(0, 59)),
+        offset(2, 0)
+    );
+    assert_eq!(
+        oslo.offset_at(date(2026, 10, 25), Clock::new(1, 0)),
+        offset(1, 0)
+    );
+}
+
+#[test]
+fn test_zone_transitions_in_a_year() {
+    let tzdata = TzData::bundled();
+    assert_eq!(
+        tzdata.zone("Europe/Oslo").unwrap().transitions(2026),
+        [
+            Transition {
+                date: date(2026, 3, 29),
+                clock: Clock::new(2, 0),
+                from: offset(1, 0),
+                to: offset(2, 0),
+            },
+            Transition {
+                date: date(2026, 10, 25),
+                clock: Clock::new(3, 0),
+                from: offset(2, 0),
+                to: offset(1, 0),
+            },
+        ]
+    );
+    assert!(tzdata
+        .zone("Asia/Tokyo")
+        .unwrap()
+        .transitions(2026)
+        .is_empty());
+}
+
+#[test]
+fn test_zone_transition_on_a_date() {
+    let tzdata = TzData::bundled();
+    let new_york = tzdata.zone("America/New_York").unwrap();
+    assert_eq!(
+        new_york.transition_on(date(2026, 3, 8)),
+        Some(Transition {
+            date: date(2026, 3, 8),
+            clock: Clock::new(2, 0),
+            from: offset(-5, 0),
+            to: offset(-4, 0),
+        })
+    );
+    assert_eq!(
+        new_york.transition_on(date(2026, 11, 1)).unwrap().to,
+        offset(-5, 0)
+    );
+    assert_eq!(new_york.transition_on(date(2026, 3, 9)), None);
+}
+
+#[test]
+fn test_southern_hemisphere_daylight_saving_spans_the_new_year() {
+    let tzdata = TzData::bundled();
+    let sydney = tzdata.zone("Australia/Sydney").unwrap();
+    assert_eq!(
+        sydney.offset_at(date(2026, 1, 1), Clock::new(0, 0)),
+        offset(11, 0)
+    );
+    assert_eq!(
+        sydney.offset_at(date(2026, 7, 1), Clock::new(0, 0)),
+        offset(10, 0)
+    );
+    let transitions = sydney.transitions(2026);
+    assert_eq!(
+        transitions.iter().map(|t| t.date).collect::<Vec<_>>(),
+        [date(2026, 4, 5), date(2026, 10, 4)]
+    );
+    assert_eq!(transitions[0].clock, Clock::new(3, 0));
+}
+
+#[test]
+fn test_zone_with_negative_daylight_saving() {
+    let tzdata = TzData::bundled();
+    let dublin = tzdata.zone("Europe/Dublin").unwrap();
+    assert_eq!(
+        dublin.offset_at(date(2026, 1, 15), Clock::new(12, 0)),
+        UtcOffset::UTC
+    );
+    assert_eq!(
+        dublin.abbreviation_at(date(2026, 1, 15), Clock::new(12, 0)),
+        "GMT"
+    );
+    assert_eq!(
+        dublin.offset_at(date(2026, 7, 15), Clock::new(12, 0)),
+        offset(1, 0)
+    );
+    assert_eq!(
+        dublin.abbreviation_at(date(2026, 7, 15), Clock::new(12, 0)),
+        "IST"
+    );
+}
+
+#[test]
+fn test_zone_converts_utc_to_local_date_and_clock() {
+    let tzdata = TzData::bundled();
+    let new_york = tzdata.zone("America/New_York").unwrap();
+    let (local_date, local) = new_york.to_local(date(2026, 3, 8), Clock::new(3, 0));
+    assert_eq!(local_date, date(2026, 3, 7));
+    assert_eq!(local.to_string(), "22:00-05:00");
+
+    let kathmandu = tzdata.zone("Asia/Kathmandu").unwrap();
+    let (local_date, local) = kathmandu.to_local(date(2026, 12, 31), Clock::new(18, 15));
+    assert_eq!(local_date, date(2027, 1, 1));
+    assert_eq!(local.to_string(), "00:00+05:45");
+    assert_eq!(
+        kathmandu.abbreviation_at(date(2026, 12, 31), Clock::new(0, 0)),
+        "+0545"
+    );
+}
+
+#[test]
+fn test_parse_tzdata() {
+    let tzdata: TzData = "# Comment\n\nTest/Half  ABC-4:30DEF-5,M4.1.0/0,M9.1.0/-1\n"
+        .parse()
+        .unwrap();
+    let zone = tzdata.zone("Test/Half").unwrap();
+    assert_eq!(zone.standard_offset(), offset(4, 30));
+    assert_eq!(
+        zone.offset_at(date(2026, 6, 1), Clock::new(0, 0)),
+        offset(5, 0)
+    );
+    assert_eq!(zone.transitions(2026)[1].date, date(2026, 9, 5));
+    assert_eq!(zone.transitions(2026)[1].clock, Clock::new(23, 0));
+}
+
+#[test]
+fn test_parse_tzdata_errors() {
+    let error = |line, kind| Err::<TzData, _>(ParseZoneError { line, kind });
+    assert_eq!(
+        "UTC UTC0\nBad/Zone".parse(),
+        error(2, ParseZoneErrorKind::MissingRule)
+    );
+    assert_eq!(
+        "Bad/Zone CET".parse(),
+        error(1, ParseZoneErrorKind::BadOffset)
+    );
+    assert_eq!(
+        "Bad/Zone CET-25".parse(),
+        error(1, ParseZoneErrorKind::BadOffset)
+    );
+    assert_eq!(
+        "Bad/Zone X0".parse(),
+        error(1, ParseZoneErrorKind::BadAbbreviation)
+    );
+    assert_eq!(
+        "Bad/Zone CET-1CEST".parse(),
+        error(1, ParseZoneErrorKind::MissingRule)
+    );
+    assert_eq!(
+        "Bad/Zone CET-1CEST,M3.5.0,M13.5.0".parse(),
+        error(1, ParseZoneErrorKind::BadRule)
+    );
+    assert_eq!(
+        "Bad/Zone CET-1CEST,J60,J300".parse(),
+        error(1, ParseZoneErrorKind::UnsupportedRule)
+    );
+    assert_eq!(
+        "A/B UTC0\nA/B UTC0".parse(),
+        error(2, ParseZoneErrorKind::DuplicateZone("A/B".to_string()))
+    );
+}
+
+//
+// Alarms
+//
+
+#[test]
+fn test_weekday_masks() {
+    let weekend = Weekday::Saturday | Weekday::Sunday;
+    assert_eq!(weekend, WeekdayMask::WEEKEND);
+    assert_eq!(WeekdayMask::WEEKDAYS | weekend, WeekdayMask::EVERY_DAY);
+    assert!(WeekdayMask::WEEKDAYS.contains(Weekday::Friday));
+    assert!(!WeekdayMask::WEEKDAYS.contains(Weekday::Saturday));
+
+    let mut mask: WeekdayMask = [Weekday::Monday, Weekday::Wednesday].into_iter().collect();
+    mask.remove(Weekday::Monday);
+    assert_eq!(mask, WeekdayMask::from(Weekday::Wednesday));
+    mask.remove(Weekday::Wednesday);
+    assert!(mask.is_empty());
+}
+
+fn scheduler_at(date: Date, clock: Clock) -> (Scheduler<ManualTimeSource>, ManualTimeSource) {
+    let source = ManualTimeSource::new(date, clock);
+    (Scheduler::new(source.clone()), source)
+}
+
+#[test]
+fn test_next_alarm_after_a_time() {
+    // 2026-10-19 is a Monday
+    let (mut scheduler, _) = scheduler_at(date(2026, 10, 19), Clock::new(0, 0));
+    let work = scheduler.add(Clock::new(7, 0), WeekdayMask::WEEKDAYS);
+    let lie_in = scheduler.add(Clock::new(9, 30), WeekdayMask::WEEKEND);
+
+    let next = scheduler.next_after(date(2026, 10, 19), Clock::new(6, 0));
+    assert_eq!(
+        next,
+        Some(Occurrence {
+            alarm: work,
+            date: date(2026, 10, 19),
+            clock: Clock::new(7, 0),
+        })
+    );
+    // Not at exactly the time given, but the next day
+    let next = scheduler
+        .next_after(date(2026, 10, 19), Clock::new(7, 0))
+        .unwrap();
+    assert_eq!((next.alarm, next.date), (work, date(2026, 10, 20)));
+    // Over the weekend
+    let next = scheduler
+        .next_after(date(2026, 10, 23), Clock::new(8, 0))
+        .unwrap();
+    assert_eq!((next.alarm, next.date), (lie_in, date(2026, 10, 24)));
+}
+
+#[test]
+fn test_next_alarm_a_week_away() {
+    let (mut scheduler, _) = scheduler_at(date(2026, 10, 19), Clock::new(0, 0));
+    let monday = scheduler.add(Clock::new(8, 0), Weekday::Monday);
+    let next = scheduler
+        .next_after(date(2026, 10, 19), Clock::new(8, 0))
+        .unwrap();
+    assert_eq!((next.alarm, next.date), (monday, date(2026, 10, 26)));
+}
+
+#[test]
+fn test_no_next_alarm_without_days() {
+    let (mut scheduler, _) = scheduler_at(date(2026, 10, 19), Clock::new(0, 0));
+    assert_eq!(scheduler.next_firing(), None);
+    let never = scheduler.add(Clock::new(8, 0), WeekdayMask::NONE);
+    assert_eq!(scheduler.next_firing(), None);
+    assert_eq!(scheduler.upcoming().count(), 0);
+    assert!(scheduler.remove(never).is_some());
+    assert!(scheduler.remove(never).is_none());
+}
+
+#[test]
+fn test_upcoming_occurrences_in_order() {
+    let (mut scheduler, _) = scheduler_at(date(2026, 10, 23), Clock::new(12, 0));
+    let first = scheduler.add(Clock::new(18, 0), WeekdayMask::EVERY_DAY);
+    let second = scheduler.add(Clock::new(18, 0), Weekday::Friday | Weekday::Saturday);
+    let morning = scheduler.add(Clock::new(6, 0), WeekdayMask::WEEKDAYS);
+    let upcoming: Vec<_> = scheduler
+        .upcoming()
+        .take(7)
+        .map(|occurrence| (occurrence.alarm, occurrence.date.day()))
+        .collect();
+    assert_eq!(
+        upcoming,
+        [
+            (first, 23),
+            (second, 23),
+            (first, 24),
+            (second, 24),
+            (first, 25),
+            (morning, 26),
+            (first, 26),
+        ]
+    );
+}
+
+#[test]
+fn test_alarms_are_removed() {
+    let (mut scheduler, _) = scheduler_at(date(2026, 10, 19), Clock::new(0, 0));
+    let early = scheduler.add(Clock::new(6, 0), WeekdayMask::EVERY_DAY);
+    let late = scheduler.add(Clock::new(9, 0), WeekdayMask::EVERY_DAY);
+    assert_eq!(scheduler.next_firing().unwrap().alarm, early);
+    assert_eq!(scheduler.remove(early).unwrap().clock, Clock::new(6, 0));
+    assert_eq!(scheduler.alarm(early), None);
+    assert_eq!(scheduler.next_firing().unwrap().alarm, late);
+}
+
+#[test]
+fn test_manual_time_source_advances_across_days() {
+    let source = ManualTimeSource::new(date(2026, 12, 31), Clock::new(23, 0));
+    source.advance(Duration::from_secs(2 * 3600));
+    assert_eq!(source.now(), (date(2027, 1, 1), Clock::new(1, 0)));
+    source.advance(Duration::from_secs(3 * 24 * 3600));
+    assert_eq!(source.now(), (date(2027, 1, 4), Clock::new(1, 0)));
+    source.set(date(2026, 1, 1), Clock::new(0, 0));
+    assert_eq!(source.now(), (date(2026, 1, 1), Clock::new(0, 0)));
+}
+
+#[test]
+fn test_poll_fires_alarms_due_since_last_poll() {
+    let (mut scheduler, source) = scheduler_at(date(2026, 10, 19), Clock::new(6, 0));
+    let alarm = scheduler.add(Clock::new(7, 0), WeekdayMask::EVERY_DAY);
+    assert!(scheduler.poll().is_empty());
+
+    source.set(date(2026, 10, 19), Clock::new(7, 0));
+    let fired = scheduler.poll();
+    assert_eq!(fired.len(), 1);
+    assert_eq!((fired[0].alarm, fired[0].date), (alarm, date(2026, 10, 19)));
+    assert!(scheduler.poll().is_empty());
+
+    // Every missed firing is delivered
+    source.advance(Duration::from_secs(3 * 24 * 3600));
+    let days: Vec<_> = scheduler.poll().iter().map(|o| o.date.day()).collect();
+    assert_eq!(days, [20, 21, 22]);
+}
+
+#[test]
+fn test_poll_fires_nothing_when_time_goes_back() {
+    let (mut scheduler, source) = scheduler_at(date(2026, 10, 19), Clock::new(8, 0));
+    scheduler.add(Clock::new(7, 0), WeekdayMask::EVERY_DAY);
+    source.set(date(2026, 10, 19), Clock::new(6, 0));
+    assert!(scheduler.poll().is_empty());
+    source.set(date(2026, 10, 19), Clock::new(7, 30));
+    assert!(scheduler.poll().is_empty());
+}
+
+#[test]
+fn test_firings_are_delivered_to_callbacks() {
+    let (mut scheduler, source) = scheduler_at(date(2026, 10, 19), Clock::new(0, 0));
+    let alarm = scheduler.add(Clock::new(12, 0), WeekdayMask::EVERY_DAY);
+    let delivered = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&delivered);
+    scheduler.on_fire(move |occurrence| sink.lock().unwrap().push(*occurrence));
+
+    source.advance(Duration::from_secs(36 * 3600));
+    scheduler.poll();
+    let delivered = delivered.lock().unwrap();
+    assert_eq!(delivered.len(), 2);
+    assert!(delivered.iter().all(|occurrence| occurrence.alarm == alarm));
+}
+
+#[test]
+fn test_firings_are_delivered_to_channels() {
+    let (mut scheduler, source) = scheduler_at(date(2026, 10, 19), Clock::new(0, 0));
+    let alarm = scheduler.add(Clock::new(12, 0), WeekdayMask::EVERY_DAY);
+    let receiver = scheduler.subscribe();
+    let dropped = scheduler.subscribe();
+    drop(dropped);
+
+    source.set(date(2026, 10, 19), Clock::new(12, 0));
+    assert_eq!(scheduler.poll().len(), 1);
+    let occurrence = receiver.try_recv().unwrap();
+    assert_eq!(
+        (occurrence.alarm, occurrence.clock),
+        (alarm, Clock::new(12, 0))
+    );
+    assert!(receiver.try_recv().is_err());
+}
+
+#[test]
+fn test_system_time_source() {
+    let (date, _) = SystemTimeSource::utc().now();
+    assert!(date >= Date::new(2024, 1, 1).unwrap());
+    let (east, _) = SystemTimeSource::new(UtcOffset::from_hours(14).unwrap()).now();
+    let (west, _) = SystemTimeSource::new(UtcOffset::from_hours(-12).unwrap()).now();
+    assert!(east > west);
+}
@@ -0,0 +1,137 @@
+//! Anniversaries counted in the SI seconds which really elapse, including the
+//! leap seconds from the bundled `data/leap-seconds.list` table.
+use time::{Date, OffsetDateTime};
+
+use crate::{after_offset, seconds_per_unit, Error, Unit};
+
+const LEAP_SECONDS: &str = include_str!("../data/leap-seconds.list");
+
+/// Seconds from the NTP epoch, 1900-01-01, to the Unix epoch, 1970-01-01.
+const NTP_TO_UNIX: i64 = 2_208_988_800;
+
+/// The result of `after_elapsed`, with the civil answer to compare against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Elapsed {
+    /// The answer counting every day as 86 400 seconds, as `after_offset` does.
+    pub civil: OffsetDateTime,
+    /// The answer counting the SI seconds which really elapse. This is earlier
+    /// than `civil` by one second for every leap second passed on the way.
+    pub elapsed: OffsetDateTime,
+    /// Whether the elapsed answer is during a leap second, `23:59:60` UTC,
+    /// which `OffsetDateTime` cannot represent. If so, `elapsed` is the second
+    /// before it.
+    pub on_leap_second: bool,
+    /// The UTC dates which ended with a leap second between the start and the
+    /// elapsed answer.
+    pub leap_seconds: Vec<Date>,
+}
+
+impl Elapsed {
+    /// Whether counting leap seconds gives a different answer.
+    pub fn differs(&self) -> bool {
+        self.civil != self.elapsed || self.on_leap_second
+    }
+}
+
+/// Returns the moment `amount` units after `start`, counting the SI seconds
+/// which really elapse, along with the answer from `after_offset`.
+///
+/// Seconds up to weeks are counted as that many SI seconds, so each leap
+/// second passed makes the answer one second earlier on the civil clock, or
+/// one second later when going back in time. Months and years follow the
+/// calendar, so leap seconds do not change them. Leap seconds before 1972,
+/// when UTC was adjusted by fractions of a second instead, are not counted.
+pub fn after_elapsed(start: OffsetDateTime, amount: i64, unit: Unit) -> Result<Elapsed, Error> {
+    let civil = after_offset(start, amount, unit)?;
+    let seconds = match seconds_per_unit(unit) {
+        Some(seconds_per_unit) => amount
+            .checked_mul(seconds_per_unit)
+            .ok_or(Error::Overflow)?,
+        None => {
+            return Ok(Elapsed {
+                civil,
+                elapsed: civil,
+                on_leap_second: false,
+                leap_seconds: Vec::new(),
+            })
+        }
+    };
+
+    let from = start.unix_timestamp();
+    // Leap seconds are crossed in the direction of travel, and each is one
+    // more second to count before reaching the same civil time
+    let leaps = leap_second_ends();
+    let crossed: Vec<i64> = if seconds >= 0 {
+        leaps.into_iter().filter(|&end| end > from).collect()
+    } else {
+        leaps.into_iter().rev().filter(|&end| end <= from).collect()
+    };
+    let distance = seconds.unsigned_abs() as i64;
+    let mut passed = Vec::new();
+    let mut on_leap_second = false;
+    for end in crossed {
+        // SI seconds from the start to the beginning of this leap second
+        let to_leap = if seconds >= 0 {
+            end - from + passed.len() as i64
+        } else {
+            from - end + passed.len() as i64 + 1
+        };
+        if distance < to_leap {
+            break;
+        }
+        passed.push(end);
+        if distance == to_leap {
+            on_leap_second = true;
+            break;
+        }
+    }
+
+    let elapsed = if on_leap_second {
+        // The second before the leap second
+        passed.last().unwrap() - 1
+    } else if seconds >= 0 {
+        from + distance - passed.len() as i64
+    } else {
+        from - distance + passed.len() as i64
+    };
+    let elapsed = OffsetDateTime::from_unix_timestamp(elapsed)
+        .map_err(|_| Error::Overflow)?
+        .replace_nanosecond(start.nanosecond())
+        .map_err(|_| Error::Overflow)?
+        .to_offset(start.offset());
+    let leap_seconds = passed
+        .iter()
+        .map(|&end| OffsetDateTime::from_unix_timestamp(end - 1).unwrap().date())
+        .collect();
+    Ok(Elapsed {
+        civil,
+        elapsed,
+        on_leap_second,
+        leap_seconds,
+    })
+}
+
+/// The Unix times just after each leap second, in order.
+fn leap_second_ends() -> Vec<i64> {
+    let mut ends = Vec::new();
+    let mut previous = None;
+    for line in LEAP_SECONDS.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        let (Some(ntp), Some(difference)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let ntp: i64 = ntp.parse().expect("the leap second table is valid");
+        let difference: i64 = difference.parse().expect("the leap second table is valid");
+        if let Some(previous) = previous {
+            assert_eq!(
+                difference - previous,
+                1,
+                "only leap seconds which add a second are supported"
+            );
+            ends.push(ntp - NTP_TO_UNIX);
+        }
+        previous = Some(difference);
+    }
+    ends
+}
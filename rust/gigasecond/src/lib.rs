@@ -1,7 +1,103 @@
 use time::Duration;
+use time::OffsetDateTime;
 use time::PrimitiveDateTime as DateTime;
 
-// Returns a DateTime one billion seconds after start.
-pub fn after(start: DateTime) -> DateTime {
-    start.saturating_add(Duration::seconds(1_000_000_000))
+mod leap;
+mod milestone;
+
+pub use leap::{after_elapsed, Elapsed};
+pub use milestone::{milestones, Milestone, MilestoneKind, Milestones};
+
+/// One billion seconds, the amount for the classic gigasecond anniversary.
+pub const GIGASECOND: i64 = 1_000_000_000;
+
+/// A unit of time to add to a date and time.
+///
+/// Seconds up to weeks are fixed lengths of time. Months and years follow the
+/// calendar instead, keeping the time of day and the day of the month, except
+/// that a day past the end of the month is clamped to its last day: one month
+/// after 31 January is 28 or 29 February, and one year after 29 February is 28
+/// February.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The result is outside the range of dates that can be represented.
+    Overflow,
+}
+
+/// Returns the date and time `amount` units after `start`, or before it if
+/// `amount` is negative.
+pub fn after(start: DateTime, amount: i64, unit: Unit) -> Result<DateTime, Error> {
+    let Some(seconds_per_unit) = seconds_per_unit(unit) else {
+        let months = match unit {
+            Unit::Year => amount.checked_mul(12).ok_or(Error::Overflow)?,
+            _ => amount,
+        };
+        return add_months(start, months);
+    };
+    let seconds = amount
+        .checked_mul(seconds_per_unit)
+        .ok_or(Error::Overflow)?;
+    start
+        .checked_add(Duration::seconds(seconds))
+        .ok_or(Error::Overflow)
+}
+
+/// Returns the moment `amount` units after `start`, as for `after`, keeping the
+/// offset of `start`.
+///
+/// Months and years follow the calendar at that offset, so the same moment
+/// written at different offsets can give different answers for them: one
+/// month after 30 January 2023 23:00 UTC is 28 February 23:00 UTC, but the
+/// same moment at +02:00 is 31 January 01:00, and one month after that is 28
+/// February 01:00, a day earlier. Seconds up to weeks give the same moment at
+/// any offset.
+pub fn after_offset(
+    start: OffsetDateTime,
+    amount: i64,
+    unit: Unit,
+) -> Result<OffsetDateTime, Error> {
+    let local = DateTime::new(start.date(), start.time());
+    let result = after(local, amount, unit)?.assume_offset(start.offset());
+    // Check the moment is in range in UTC too, not only at the offset
+    result
+        .checked_to_offset(time::UtcOffset::UTC)
+        .map(|_| result)
+        .ok_or(Error::Overflow)
+}
+
+/// The length of a unit in seconds, or `None` for the calendar units.
+fn seconds_per_unit(unit: Unit) -> Option<i64> {
+    match unit {
+        Unit::Second => Some(1),
+        Unit::Minute => Some(60),
+        Unit::Hour => Some(60 * 60),
+        Unit::Day => Some(24 * 60 * 60),
+        Unit::Week => Some(7 * 24 * 60 * 60),
+        Unit::Month | Unit::Year => None,
+    }
+}
+
+fn add_months(start: DateTime, months: i64) -> Result<DateTime, Error> {
+    let month = (start.month() as i64 - 1)
+        .checked_add(months)
+        .ok_or(Error::Overflow)?;
+    let year = (start.year() as i64)
+        .checked_add(month.div_euclid(12))
+        .and_then(|year| i32::try_from(year).ok())
+        .ok_or(Error::Overflow)?;
+    let month = time::Month::January.nth_next(month.rem_euclid(12) as u8);
+    let day = start.day().min(month.length(year));
+    let date = time::Date::from_calendar_date(year, month, day).map_err(|_| Error::Overflow)?;
+    Ok(DateTime::new(date, start.time()))
 }
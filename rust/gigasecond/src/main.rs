@@ -0,0 +1,394 @@
+//! Prints the gigasecond anniversary of a moment, and the milestones coming up.
+//!
+//! ```text
+//! gigasecond [--json] [--offset OFFSET] [--count N] [--now DATE] DATE...
+//! ```
+//!
+//! The date may be ISO 8601, such as `2015-01-24T22:00:00+02:00`, or written
+//! out, such as `24 January 2015 22:00` or `Jan 24, 2015`. It is taken to be in
+//! UTC unless it has an offset or `--offset` is given.
+use std::env;
+use std::fmt;
+use std::process::ExitCode;
+
+use gigasecond::{after_offset, milestones, Unit, GIGASECOND};
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+const USAGE: &str = "\
+Usage: gigasecond [--json] [--offset OFFSET] [--count N] [--now DATE] DATE...
+
+Prints the moment one gigasecond after DATE, and the next milestones after now.
+
+DATE is ISO 8601, such as 2015-01-24T22:00:00+02:00, or written out, such as
+'24 January 2015 22:00' or 'Jan 24, 2015'. Without an offset it is in UTC.
+
+Options:
+  --json           Print JSON instead of text
+  --offset OFFSET  The offset of DATE, such as +02:00 or -0530
+  --count N        How many upcoming milestones to print (default 5)
+  --now DATE       Count upcoming milestones from DATE instead of now
+  -h, --help       Print this help";
+
+/// What went wrong, and so which exit code to use.
+#[derive(Debug)]
+enum Error {
+    /// The arguments or the date could not be understood.
+    Usage(String),
+    /// The answer is past the dates that can be represented.
+    Overflow,
+}
+
+impl Error {
+    fn exit_code(&self) -> u8 {
+        match self {
+            Error::Usage(_) => 2,
+            Error::Overflow => 1,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Usage(message) => write!(f, "{}", message),
+            Error::Overflow => write!(f, "the answer is past the last date that can be shown"),
+        }
+    }
+}
+
+impl From<gigasecond::Error> for Error {
+    fn from(err: gigasecond::Error) -> Self {
+        match err {
+            gigasecond::Error::Overflow => Error::Overflow,
+        }
+    }
+}
+
+fn usage(message: impl Into<String>) -> Error {
+    Error::Usage(message.into())
+}
+
+struct Options {
+    json: bool,
+    offset: Option<UtcOffset>,
+    count: usize,
+    now: Option<String>,
+    date: String,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    match parse_args(&args).and_then(|options| run(&options)) {
+        Ok(output) => {
+            println!("{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("gigasecond: {}", err);
+            if let Error::Usage(_) = err {
+                eprintln!("Try 'gigasecond --help' for more information.");
+            }
+            ExitCode::from(err.exit_code())
+        }
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Options, Error> {
+    let mut options = Options {
+        json: false,
+        offset: None,
+        count: 5,
+        now: None,
+        date: String::new(),
+    };
+    let mut date = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| usage(format!("{} needs a value", name)))
+        };
+        match arg.as_str() {
+            "--json" => options.json = true,
+            "--offset" => options.offset = Some(parse_offset(value("--offset")?)?),
+            "--count" => {
+                let count = value("--count")?;
+                options.count = count
+                    .parse()
+                    .map_err(|_| usage(format!("'{}' is not a number of milestones", count)))?;
+            }
+            "--now" => options.now = Some(value("--now")?.clone()),
+            flag if flag.starts_with("--") => {
+                return Err(usage(format!("unknown option '{}'", flag)))
+            }
+            _ => date.push(arg.as_str()),
+        }
+    }
+    if date.is_empty() {
+        return Err(usage("no date given"));
+    }
+    options.date = date.join(" ");
+    Ok(options)
+}
+
+fn run(options: &Options) -> Result<String, Error> {
+    let (start, offset) = parse_datetime(&options.date)?;
+    let offset = match (offset, options.offset) {
+        (Some(_), Some(_)) => return Err(usage("the date has an offset, and --offset was given")),
+        (offset, option) => offset.or(option).unwrap_or(UtcOffset::UTC),
+    };
+    let start = start.assume_offset(offset);
+    let gigasecond = after_offset(start, GIGASECOND, Unit::Second)?;
+
+    let now = match &options.now {
+        Some(now) => {
+            let (now, now_offset) = parse_datetime(now)?;
+            now.assume_offset(now_offset.unwrap_or(offset))
+        }
+        None => OffsetDateTime::now_utc(),
+    };
+    let now = now.to_offset(offset);
+    let upcoming: Vec<_> = milestones(local(start))
+        .between(local(now), PrimitiveDateTime::MAX)
+        .take(options.count)
+        .map(|(milestone, at)| (milestone, at.assume_offset(offset)))
+        .collect();
+
+    let output = if options.json {
+        let milestones: Vec<String> = upcoming
+            .iter()
+            .map(|(milestone, at)| {
+                let unit = format!("{:?}", milestone.unit).to_lowercase();
+                format!(
+                    "{{\"milestone\":\"{}\",\"kind\":\"{:?}\",\"amount\":{},\"unit\":\"{}\",\"at\":\"{}\"}}",
+                    milestone,
+                    milestone.kind,
+                    milestone.amount,
+                    unit,
+                    iso(at)
+                )
+            })
+            .collect();
+        format!(
+            "{{\"start\":\"{}\",\"gigasecond\":\"{}\",\"milestones\":[{}]}}",
+            iso(&start),
+            iso(&gigasecond),
+            milestones.join(",")
+        )
+    } else {
+        let mut output = format!(
+            "Start:      {}\nGigasecond: {}",
+            iso(&start),
+            iso(&gigasecond)
+        );
+        if !upcoming.is_empty() {
+            output.push_str("\n\nUpcoming milestones:");
+            for (milestone, at) in &upcoming {
+                output.push_str(&format!("\n  {}  {}", iso(at), milestone));
+            }
+        }
+        output
+    };
+    Ok(output)
+}
+
+fn local(datetime: OffsetDateTime) -> PrimitiveDateTime {
+    PrimitiveDateTime::new(datetime.date(), datetime.time())
+}
+
+/// Formats as ISO 8601, such as `2046-10-02T23:46:40+02:00`.
+fn iso(datetime: &OffsetDateTime) -> String {
+    let mut iso = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        datetime.year(),
+        datetime.month() as u8,
+        datetime.day(),
+        datetime.hour(),
+        datetime.minute(),
+        datetime.second()
+    );
+    if datetime.nanosecond() != 0 {
+        let fraction = format!("{:09}", datetime.nanosecond());
+        iso.push('.');
+        iso.push_str(fraction.trim_end_matches('0'));
+    }
+    let offset = datetime.offset();
+    if offset.is_utc() {
+        iso.push('Z');
+    } else {
+        let sign = if offset.is_negative() { '-' } else { '+' };
+        let (hours, minutes, _) = offset.as_hms();
+        iso.push_str(&format!(
+            "{}{:02}:{:02}",
+            sign,
+            hours.unsigned_abs(),
+            minutes.unsigned_abs()
+        ));
+    }
+    iso
+}
+
+/// Parses a date, then an optional time of day, then an optional offset.
+fn parse_datetime(s: &str) -> Result<(PrimitiveDateTime, Option<UtcOffset>), Error> {
+    let s = s.trim();
+    let (date, rest) = parse_date(s)?;
+    let rest = rest.trim_start_matches(|c: char| c == 'T' || c == 't' || c.is_whitespace());
+    let (time, rest) = if rest.starts_with(|c: char| c.is_ascii_digit()) {
+        parse_time(rest)?
+    } else {
+        (Time::MIDNIGHT, rest)
+    };
+    let rest = rest.trim();
+    let offset = if rest.is_empty() {
+        None
+    } else {
+        Some(parse_offset(rest)?)
+    };
+    Ok((PrimitiveDateTime::new(date, time), offset))
+}
+
+/// Parses `YYYY-MM-DD`, `YYYY/MM/DD`, `24 Jan 2015` or `Jan 24, 2015`,
+/// returning the rest of the input.
+fn parse_date(s: &str) -> Result<(Date, &str), Error> {
+    let unrecognised = || {
+        usage(format!(
+            "'{}' is not a date; expected one like 2015-01-24, 24 Jan 2015 or Jan 24, 2015",
+            s
+        ))
+    };
+    let (first, rest) = split_word(s);
+    let (year, month, day, rest) = if let Some(month) = parse_month(first) {
+        // Jan 24, 2015
+        let (day, rest) = split_word(rest);
+        let (year, rest) = split_word(rest);
+        (year, month, day.trim_end_matches(','), rest)
+    } else if first.len() <= 2 {
+        // 24 Jan 2015
+        let (month, rest) = split_word(rest);
+        let (year, rest) = split_word(rest);
+        (
+            year,
+            parse_month(month).ok_or_else(unrecognised)?,
+            first,
+            rest,
+        )
+    } else {
+        // 2015-01-24, followed by the time without a space in between
+        let date_len = s
+            .find(|c: char| !(c.is_ascii_digit() || c == '-' || c == '/'))
+            .unwrap_or(s.len());
+        let (date, rest) = s.split_at(date_len);
+        let parts: Vec<&str> = date.split(['-', '/']).collect();
+        match parts[..] {
+            [year, month, day] if year.len() == 4 && month.len() == 2 && day.len() == 2 => {
+                let month = month
+                    .parse::<u8>()
+                    .ok()
+                    .and_then(|month| Month::try_from(month).ok());
+                (year, month.ok_or_else(unrecognised)?, day, rest)
+            }
+            _ => return Err(unrecognised()),
+        }
+    };
+    let year: i32 = year.parse().map_err(|_| unrecognised())?;
+    let day: u8 = day.parse().map_err(|_| unrecognised())?;
+    let date = Date::from_calendar_date(year, month, day)
+        .map_err(|_| usage(format!("{} {} has no day {}", month, year, day)))?;
+    Ok((date, rest))
+}
+
+fn split_word(s: &str) -> (&str, &str) {
+    let s = s.trim_start();
+    s.split_at(s.find(char::is_whitespace).unwrap_or(s.len()))
+}
+
+/// Parses a month name or its first three letters, in any case.
+fn parse_month(s: &str) -> Option<Month> {
+    let s = s.trim_end_matches(['.', ',']).to_ascii_lowercase();
+    if s.len() < 3 {
+        return None;
+    }
+    let mut month = Month::January;
+    for _ in 0..12 {
+        if month.to_string().to_ascii_lowercase().starts_with(&s) {
+            return Some(month);
+        }
+        month = month.next();
+    }
+    None
+}
+
+/// Parses `HH:MM`, `HH:MM:SS` or `HH:MM:SS.fff`, returning the rest of the input.
+fn parse_time(s: &str) -> Result<(Time, &str), Error> {
+    let time_len = s
+        .find(|c: char| !(c.is_ascii_digit() || c == ':' || c == '.' || c == ','))
+        .unwrap_or(s.len());
+    let (text, rest) = s.split_at(time_len);
+    let invalid = || {
+        usage(format!(
+            "'{}' is not a time; expected one like 22:00 or 22:00:00",
+            text
+        ))
+    };
+    let (text_hms, fraction) = match text.split_once(['.', ',']) {
+        Some((hms, fraction)) => (hms, Some(fraction)),
+        None => (text, None),
+    };
+    let parts: Vec<u8> = text_hms
+        .split(':')
+        .map(|part| part.parse().map_err(|_| invalid()))
+        .collect::<Result<_, _>>()?;
+    let (hour, minute, second) = match parts[..] {
+        [hour, minute] => (hour, minute, 0),
+        [hour, minute, second] => (hour, minute, second),
+        _ => return Err(invalid()),
+    };
+    let nanosecond = match fraction {
+        Some(fraction) if (1..=9).contains(&fraction.len()) => {
+            let digits: u32 = fraction.parse().map_err(|_| invalid())?;
+            digits * 10u32.pow(9 - fraction.len() as u32)
+        }
+        Some(_) => return Err(invalid()),
+        None => 0,
+    };
+    let time = Time::from_hms_nano(hour, minute, second, nanosecond).map_err(|_| invalid())?;
+    Ok((time, rest))
+}
+
+/// Parses `Z`, `UTC`, or a signed `HH`, `HH:MM` or `HHMM`.
+fn parse_offset(s: &str) -> Result<UtcOffset, Error> {
+    let invalid = || {
+        usage(format!(
+            "'{}' is not an offset; expected one like +02:00 or -0530",
+            s
+        ))
+    };
+    if s.eq_ignore_ascii_case("z") || s.eq_ignore_ascii_case("utc") {
+        return Ok(UtcOffset::UTC);
+    }
+    let (sign, digits) = match s.split_at_checked(1) {
+        Some(("+", digits)) => (1, digits),
+        Some(("-", digits)) => (-1, digits),
+        _ => return Err(invalid()),
+    };
+    let digits = digits.replacen(':', "", 1);
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let (hours, minutes) = match digits.len() {
+        2 => (&digits[..], "0"),
+        4 => digits.split_at(2),
+        _ => return Err(invalid()),
+    };
+    let hours: i8 = hours
+        .parse()
+        .ok()
+        .filter(|hours| *hours < 24)
+        .ok_or_else(invalid)?;
+    let minutes: i8 = minutes.parse().map_err(|_| invalid())?;
+    UtcOffset::from_hms(sign * hours, sign * minutes, 0).map_err(|_| invalid())
+}
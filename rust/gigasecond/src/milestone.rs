@@ -0,0 +1,196 @@
+//! Round-number anniversaries of a moment, such as each gigasecond or each
+//! 10 000 days, in the order they happen.
+use std::fmt;
+
+use time::PrimitiveDateTime as DateTime;
+
+use crate::{after, Unit, GIGASECOND};
+
+/// A series of milestones, each a whole number of steps after the start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MilestoneKind {
+    /// Every billion seconds.
+    Gigaseconds,
+    /// 10 seconds, 100 seconds, 1 000 seconds and so on.
+    PowersOfTenSeconds,
+    /// Every million minutes.
+    MillionMinutes,
+    /// Every 100 000 hours.
+    HundredThousandHours,
+    /// Every 10 000 days.
+    TenThousandDays,
+    /// Every 1 000 weeks.
+    ThousandWeeks,
+    /// Every 100 months.
+    HundredMonths,
+}
+
+impl MilestoneKind {
+    pub const ALL: [MilestoneKind; 7] = [
+        MilestoneKind::Gigaseconds,
+        MilestoneKind::PowersOfTenSeconds,
+        MilestoneKind::MillionMinutes,
+        MilestoneKind::HundredThousandHours,
+        MilestoneKind::TenThousandDays,
+        MilestoneKind::ThousandWeeks,
+        MilestoneKind::HundredMonths,
+    ];
+
+    fn unit(self) -> Unit {
+        match self {
+            MilestoneKind::Gigaseconds | MilestoneKind::PowersOfTenSeconds => Unit::Second,
+            MilestoneKind::MillionMinutes => Unit::Minute,
+            MilestoneKind::HundredThousandHours => Unit::Hour,
+            MilestoneKind::TenThousandDays => Unit::Day,
+            MilestoneKind::ThousandWeeks => Unit::Week,
+            MilestoneKind::HundredMonths => Unit::Month,
+        }
+    }
+
+    /// The amount of the `n`th milestone of the series, counting from 1.
+    fn amount(self, n: u32) -> Option<i64> {
+        let step = match self {
+            MilestoneKind::PowersOfTenSeconds => return 10i64.checked_pow(n),
+            MilestoneKind::Gigaseconds => GIGASECOND,
+            MilestoneKind::MillionMinutes => 1_000_000,
+            MilestoneKind::HundredThousandHours => 100_000,
+            MilestoneKind::TenThousandDays => 10_000,
+            MilestoneKind::ThousandWeeks => 1_000,
+            MilestoneKind::HundredMonths => 100,
+        };
+        step.checked_mul(n as i64)
+    }
+}
+
+/// A round number of units after the start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Milestone {
+    pub kind: MilestoneKind,
+    pub amount: i64,
+    pub unit: Unit,
+}
+
+/// Formats as the amount and unit, such as `10,000 days`, or `2 gigaseconds`
+/// for the gigasecond series.
+impl fmt::Display for Milestone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (amount, unit) = match self.kind {
+            MilestoneKind::Gigaseconds => (self.amount / GIGASECOND, "gigasecond"),
+            _ => (
+                self.amount,
+                match self.unit {
+                    Unit::Second => "second",
+                    Unit::Minute => "minute",
+                    Unit::Hour => "hour",
+                    Unit::Day => "day",
+                    Unit::Week => "week",
+                    Unit::Month => "month",
+                    Unit::Year => "year",
+                },
+            ),
+        };
+        let plural = if amount == 1 { "" } else { "s" };
+        write!(f, "{} {}{}", group_thousands(amount), unit, plural)
+    }
+}
+
+/// Writes a number with commas between groups of three digits.
+fn group_thousands(n: i64) -> String {
+    let digits = n.unsigned_abs().to_string();
+    let mut grouped = String::with_capacity(digits.len() * 4 / 3 + 1);
+    if n < 0 {
+        grouped.push('-');
+    }
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Returns every milestone after `start` in the order they happen, up to the
+/// last date that can be represented. Milestones at the same moment, such as
+/// the first gigasecond and 10⁹ seconds, come in the order of their kinds.
+pub fn milestones(start: DateTime) -> Milestones {
+    Milestones::new(start, &MilestoneKind::ALL)
+}
+
+/// An iterator of milestones and when they happen, from `milestones`.
+#[derive(Debug, Clone)]
+pub struct Milestones {
+    start: DateTime,
+    // The next milestone of each series still going, with its number in the
+    // series and when it happens
+    next: Vec<(MilestoneKind, u32, DateTime)>,
+    until: Option<DateTime>,
+}
+
+impl Milestones {
+    fn new(start: DateTime, kinds: &[MilestoneKind]) -> Self {
+        let mut milestones = Milestones {
+            start,
+            next: Vec::new(),
+            until: None,
+        };
+        for &kind in kinds {
+            milestones.push_from(kind, 1);
+        }
+        milestones
+    }
+
+    /// Only the milestones of `kinds`.
+    pub fn kinds(mut self, kinds: impl IntoIterator<Item = MilestoneKind>) -> Self {
+        let kinds: Vec<MilestoneKind> = kinds.into_iter().collect();
+        self.next.retain(|(kind, _, _)| kinds.contains(kind));
+        self
+    }
+
+    /// Only the milestones from `from`, up to but not including `to`.
+    pub fn between(mut self, from: DateTime, to: DateTime) -> Self {
+        for (kind, mut n, _) in std::mem::take(&mut self.next) {
+            while self.at(kind, n).is_some_and(|at| at < from) {
+                n += 1;
+            }
+            self.push_from(kind, n);
+        }
+        self.until = Some(self.until.map_or(to, |until| until.min(to)));
+        self
+    }
+
+    /// When the `n`th milestone of `kind` happens, if it can be represented.
+    fn at(&self, kind: MilestoneKind, n: u32) -> Option<DateTime> {
+        after(self.start, kind.amount(n)?, kind.unit()).ok()
+    }
+
+    fn push_from(&mut self, kind: MilestoneKind, n: u32) {
+        if let Some(at) = self.at(kind, n) {
+            self.next.push((kind, n, at));
+        }
+    }
+}
+
+impl Iterator for Milestones {
+    type Item = (Milestone, DateTime);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (i, &(kind, n, at)) = self
+            .next
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, &(kind, _, at))| (at, kind))?;
+        if self.until.is_some_and(|until| at >= until) {
+            self.next.clear();
+            return None;
+        }
+        self.next.swap_remove(i);
+        self.push_from(kind, n + 1);
+        let milestone = Milestone {
+            kind,
+            amount: kind.amount(n).unwrap(),
+            unit: kind.unit(),
+        };
+        Some((milestone, at))
+    }
+}
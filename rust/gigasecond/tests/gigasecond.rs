@@ -1,4 +1,8 @@
+use std::process::{Command, Output};
+
+use gigasecond::{Error, Milestone, MilestoneKind, Unit, GIGASECOND};
 use time::PrimitiveDateTime as DateTime;
+use time::{OffsetDateTime, UtcOffset};
 
 /// Create a datetime from the given numeric point in time.
 ///
@@ -16,33 +20,513 @@ fn dt(year: i32, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> DateTi
 fn test_date() {
     let start_date = dt(2011, 4, 25, 0, 0, 0);
 
-    assert_eq!(gigasecond::after(start_date), dt(2043, 1, 1, 1, 46, 40));
+    assert_eq!(
+        gigasecond::after(start_date, GIGASECOND, Unit::Second),
+        Ok(dt(2043, 1, 1, 1, 46, 40))
+    );
 }
 
 #[test]
 fn test_another_date() {
     let start_date = dt(1977, 6, 13, 0, 0, 0);
 
-    assert_eq!(gigasecond::after(start_date), dt(2009, 2, 19, 1, 46, 40));
+    assert_eq!(
+        gigasecond::after(start_date, GIGASECOND, Unit::Second),
+        Ok(dt(2009, 2, 19, 1, 46, 40))
+    );
 }
 
 #[test]
 fn test_third_date() {
     let start_date = dt(1959, 7, 19, 0, 0, 0);
 
-    assert_eq!(gigasecond::after(start_date), dt(1991, 3, 27, 1, 46, 40));
+    assert_eq!(
+        gigasecond::after(start_date, GIGASECOND, Unit::Second),
+        Ok(dt(1991, 3, 27, 1, 46, 40))
+    );
 }
 
 #[test]
 fn test_datetime() {
     let start_date = dt(2015, 1, 24, 22, 0, 0);
 
-    assert_eq!(gigasecond::after(start_date), dt(2046, 10, 2, 23, 46, 40));
+    assert_eq!(
+        gigasecond::after(start_date, GIGASECOND, Unit::Second),
+        Ok(dt(2046, 10, 2, 23, 46, 40))
+    );
 }
 
 #[test]
 fn test_another_datetime() {
     let start_date = dt(2015, 1, 24, 23, 59, 59);
 
-    assert_eq!(gigasecond::after(start_date), dt(2046, 10, 3, 1, 46, 39));
+    assert_eq!(
+        gigasecond::after(start_date, GIGASECOND, Unit::Second),
+        Ok(dt(2046, 10, 3, 1, 46, 39))
+    );
+}
+
+#[test]
+fn test_fixed_units() {
+    let start = dt(2015, 1, 24, 22, 0, 0);
+    let after = |amount, unit| gigasecond::after(start, amount, unit);
+
+    assert_eq!(after(90, Unit::Minute), Ok(dt(2015, 1, 24, 23, 30, 0)));
+    assert_eq!(after(3, Unit::Hour), Ok(dt(2015, 1, 25, 1, 0, 0)));
+    assert_eq!(after(10_000, Unit::Day), Ok(dt(2042, 6, 11, 22, 0, 0)));
+    assert_eq!(after(2, Unit::Week), Ok(dt(2015, 2, 7, 22, 0, 0)));
+    assert_eq!(after(-1, Unit::Second), Ok(dt(2015, 1, 24, 21, 59, 59)));
+}
+
+#[test]
+fn test_days_count_leap_days() {
+    let start = dt(2024, 2, 28, 12, 0, 0);
+
+    assert_eq!(
+        gigasecond::after(start, 1, Unit::Day),
+        Ok(dt(2024, 2, 29, 12, 0, 0))
+    );
+    assert_eq!(
+        gigasecond::after(start, 366, Unit::Day),
+        Ok(dt(2025, 2, 28, 12, 0, 0))
+    );
+}
+
+#[test]
+fn test_months_keep_the_day_and_time() {
+    let start = dt(2015, 1, 15, 8, 30, 0);
+
+    assert_eq!(
+        gigasecond::after(start, 1, Unit::Month),
+        Ok(dt(2015, 2, 15, 8, 30, 0))
+    );
+    assert_eq!(
+        gigasecond::after(start, 23, Unit::Month),
+        Ok(dt(2016, 12, 15, 8, 30, 0))
+    );
+    assert_eq!(
+        gigasecond::after(start, -1, Unit::Month),
+        Ok(dt(2014, 12, 15, 8, 30, 0))
+    );
+}
+
+#[test]
+fn test_months_clamp_to_the_end_of_the_month() {
+    let start = dt(2023, 1, 31, 0, 0, 0);
+
+    assert_eq!(
+        gigasecond::after(start, 1, Unit::Month),
+        Ok(dt(2023, 2, 28, 0, 0, 0))
+    );
+    assert_eq!(
+        gigasecond::after(start, 13, Unit::Month),
+        Ok(dt(2024, 2, 29, 0, 0, 0))
+    );
+    assert_eq!(
+        gigasecond::after(start, 3, Unit::Month),
+        Ok(dt(2023, 4, 30, 0, 0, 0))
+    );
+}
+
+#[test]
+fn test_years_from_a_leap_day() {
+    let start = dt(2020, 2, 29, 6, 0, 0);
+
+    assert_eq!(
+        gigasecond::after(start, 1, Unit::Year),
+        Ok(dt(2021, 2, 28, 6, 0, 0))
+    );
+    assert_eq!(
+        gigasecond::after(start, 4, Unit::Year),
+        Ok(dt(2024, 2, 29, 6, 0, 0))
+    );
+    assert_eq!(
+        gigasecond::after(start, 80, Unit::Year),
+        Ok(dt(2100, 2, 28, 6, 0, 0))
+    );
+    assert_eq!(
+        gigasecond::after(start, -20, Unit::Year),
+        Ok(dt(2000, 2, 29, 6, 0, 0))
+    );
+}
+
+#[test]
+fn test_overflow_is_an_error() {
+    let start = dt(2015, 1, 24, 22, 0, 0);
+
+    assert_eq!(
+        gigasecond::after(start, 8_000, Unit::Year),
+        Err(Error::Overflow)
+    );
+    assert_eq!(
+        gigasecond::after(start, -13_000, Unit::Year),
+        Err(Error::Overflow)
+    );
+    assert_eq!(
+        gigasecond::after(start, 1_000 * GIGASECOND, Unit::Second),
+        Err(Error::Overflow)
+    );
+    assert_eq!(
+        gigasecond::after(start, i64::MAX, Unit::Minute),
+        Err(Error::Overflow)
+    );
+    assert_eq!(
+        gigasecond::after(start, i64::MAX, Unit::Year),
+        Err(Error::Overflow)
+    );
+    assert_eq!(
+        gigasecond::after(start, i64::MIN, Unit::Month),
+        Err(Error::Overflow)
+    );
+}
+
+/// Create an offset datetime from the given numeric point in time and offset in hours.
+fn odt(
+    year: i32,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    offset: i8,
+) -> OffsetDateTime {
+    dt(year, month, day, hour, minute, second)
+        .assume_offset(UtcOffset::from_hms(offset, 0, 0).unwrap())
+}
+
+fn date(year: i32, month: u8, day: u8) -> time::Date {
+    time::Date::from_calendar_date(year, month.try_into().unwrap(), day).unwrap()
+}
+
+#[test]
+fn test_offset_datetime_keeps_the_offset() {
+    let start = odt(2015, 1, 24, 22, 0, 0, 2);
+    let after = gigasecond::after_offset(start, GIGASECOND, Unit::Second).unwrap();
+
+    assert_eq!(after, odt(2046, 10, 2, 23, 46, 40, 2));
+    assert_eq!(after.offset(), start.offset());
+}
+
+#[test]
+fn test_same_moment_at_different_offsets_agrees() {
+    let oslo = odt(2015, 1, 24, 23, 0, 0, 1);
+    let new_york = odt(2015, 1, 24, 17, 0, 0, -5);
+
+    for unit in [Unit::Second, Unit::Hour, Unit::Day, Unit::Week] {
+        assert_eq!(
+            gigasecond::after_offset(oslo, 1_000, unit),
+            gigasecond::after_offset(new_york, 1_000, unit)
+        );
+    }
+}
+
+#[test]
+fn test_months_follow_the_calendar_at_the_offset() {
+    let utc = odt(2023, 1, 30, 23, 0, 0, 0);
+    let plus_two = utc.to_offset(UtcOffset::from_hms(2, 0, 0).unwrap());
+
+    assert_eq!(
+        gigasecond::after_offset(utc, 1, Unit::Month),
+        Ok(odt(2023, 2, 28, 23, 0, 0, 0))
+    );
+    assert_eq!(
+        gigasecond::after_offset(plus_two, 1, Unit::Month),
+        Ok(odt(2023, 2, 28, 1, 0, 0, 2))
+    );
+}
+
+#[test]
+fn test_offset_overflow_is_an_error() {
+    let start = odt(9999, 12, 31, 23, 0, 0, -5);
+
+    assert_eq!(
+        gigasecond::after_offset(start, 30, Unit::Minute),
+        Err(Error::Overflow)
+    );
+}
+
+#[test]
+fn test_elapsed_seconds_count_leap_seconds() {
+    // Twelve leap seconds were added between these two moments
+    let start = odt(1990, 1, 1, 0, 0, 0, 0);
+    let elapsed = gigasecond::after_elapsed(start, GIGASECOND, Unit::Second).unwrap();
+
+    assert_eq!(elapsed.civil, odt(2021, 9, 9, 1, 46, 40, 0));
+    assert_eq!(elapsed.elapsed, odt(2021, 9, 9, 1, 46, 28, 0));
+    assert!(elapsed.differs());
+    assert!(!elapsed.on_leap_second);
+    assert_eq!(elapsed.leap_seconds.len(), 12);
+    assert_eq!(elapsed.leap_seconds[0], date(1990, 12, 31));
+    assert_eq!(elapsed.leap_seconds[11], date(2016, 12, 31));
+}
+
+#[test]
+fn test_elapsed_seconds_without_leap_seconds_agree() {
+    let start = odt(2017, 1, 1, 0, 0, 0, 0);
+    let elapsed = gigasecond::after_elapsed(start, 10_000, Unit::Day).unwrap();
+
+    assert_eq!(elapsed.elapsed, elapsed.civil);
+    assert!(!elapsed.differs());
+    assert!(elapsed.leap_seconds.is_empty());
+}
+
+#[test]
+fn test_elapsed_seconds_landing_on_a_leap_second() {
+    // 2016-12-31 ended with 23:59:60 UTC, one second after 23:59:59 and two
+    // seconds before midnight
+    let start = odt(2016, 12, 31, 23, 59, 0, 0);
+
+    let on_leap = gigasecond::after_elapsed(start, 60, Unit::Second).unwrap();
+    assert!(on_leap.on_leap_second);
+    assert_eq!(on_leap.elapsed, odt(2016, 12, 31, 23, 59, 59, 0));
+    assert_eq!(on_leap.civil, odt(2017, 1, 1, 0, 0, 0, 0));
+    assert_eq!(on_leap.leap_seconds, [date(2016, 12, 31)]);
+
+    let after_leap = gigasecond::after_elapsed(start, 61, Unit::Second).unwrap();
+    assert!(!after_leap.on_leap_second);
+    assert_eq!(after_leap.elapsed, odt(2017, 1, 1, 0, 0, 0, 0));
+
+    let before_leap = gigasecond::after_elapsed(start, 59, Unit::Second).unwrap();
+    assert!(!before_leap.differs());
+}
+
+#[test]
+fn test_elapsed_seconds_going_back() {
+    let start = odt(2017, 1, 1, 0, 0, 1, 0);
+
+    let back = gigasecond::after_elapsed(start, -2, Unit::Second).unwrap();
+    assert!(back.on_leap_second);
+    assert_eq!(back.elapsed, odt(2016, 12, 31, 23, 59, 59, 0));
+
+    let back = gigasecond::after_elapsed(start, -3, Unit::Second).unwrap();
+    assert!(!back.on_leap_second);
+    assert_eq!(back.elapsed, odt(2016, 12, 31, 23, 59, 59, 0));
+    assert_eq!(back.civil, odt(2016, 12, 31, 23, 59, 58, 0));
+}
+
+#[test]
+fn test_elapsed_keeps_the_offset() {
+    let start = odt(2016, 12, 31, 18, 0, 0, -5);
+    let elapsed = gigasecond::after_elapsed(start, 1, Unit::Day).unwrap();
+
+    assert_eq!(elapsed.elapsed, odt(2017, 1, 1, 17, 59, 59, -5));
+    assert_eq!(elapsed.elapsed.offset(), start.offset());
+}
+
+#[test]
+fn test_elapsed_calendar_units_ignore_leap_seconds() {
+    let start = odt(2016, 6, 15, 12, 0, 0, 0);
+    let elapsed = gigasecond::after_elapsed(start, 1, Unit::Year).unwrap();
+
+    assert_eq!(elapsed.elapsed, odt(2017, 6, 15, 12, 0, 0, 0));
+    assert!(!elapsed.differs());
+}
+
+#[test]
+fn test_milestones_are_in_order() {
+    let start = dt(1990, 1, 1, 0, 0, 0);
+    let times: Vec<DateTime> = gigasecond::milestones(start).map(|(_, at)| at).collect();
+
+    assert!(times.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(times.iter().all(|&at| at > start));
+}
+
+#[test]
+fn test_first_milestones() {
+    let start = dt(2015, 1, 24, 22, 0, 0);
+    let first: Vec<String> = gigasecond::milestones(start)
+        .take(6)
+        .map(|(milestone, _)| milestone.to_string())
+        .collect();
+
+    assert_eq!(
+        first,
+        [
+            "10 seconds",
+            "100 seconds",
+            "1,000 seconds",
+            "10,000 seconds",
+            "100,000 seconds",
+            "1,000,000 seconds",
+        ]
+    );
+}
+
+#[test]
+fn test_gigasecond_milestones_use_after() {
+    let start = dt(2011, 4, 25, 0, 0, 0);
+    let gigaseconds: Vec<(Milestone, DateTime)> = gigasecond::milestones(start)
+        .kinds([MilestoneKind::Gigaseconds])
+        .take(2)
+        .collect();
+
+    assert_eq!(gigaseconds[0].0.to_string(), "1 gigasecond");
+    assert_eq!(gigaseconds[0].1, dt(2043, 1, 1, 1, 46, 40));
+    assert_eq!(gigaseconds[1].0.to_string(), "2 gigaseconds");
+    assert_eq!(
+        Ok(gigaseconds[1].1),
+        gigasecond::after(start, 2 * GIGASECOND, Unit::Second)
+    );
+}
+
+#[test]
+fn test_milestones_at_the_same_moment_come_in_order_of_kind() {
+    let start = dt(2011, 4, 25, 0, 0, 0);
+    let at_one_gigasecond: Vec<MilestoneKind> = gigasecond::milestones(start)
+        .filter(|&(_, at)| at == dt(2043, 1, 1, 1, 46, 40))
+        .map(|(milestone, _)| milestone.kind)
+        .collect();
+
+    assert_eq!(
+        at_one_gigasecond,
+        [
+            MilestoneKind::Gigaseconds,
+            MilestoneKind::PowersOfTenSeconds
+        ]
+    );
+}
+
+#[test]
+fn test_milestones_of_some_kinds() {
+    let start = dt(2000, 1, 1, 0, 0, 0);
+    let milestones: Vec<(String, DateTime)> = gigasecond::milestones(start)
+        .kinds([MilestoneKind::TenThousandDays, MilestoneKind::ThousandWeeks])
+        .take(3)
+        .map(|(milestone, at)| (milestone.to_string(), at))
+        .collect();
+
+    assert_eq!(
+        milestones,
+        [
+            ("1,000 weeks".to_string(), dt(2019, 3, 2, 0, 0, 0)),
+            ("10,000 days".to_string(), dt(2027, 5, 19, 0, 0, 0)),
+            ("2,000 weeks".to_string(), dt(2038, 5, 1, 0, 0, 0)),
+        ]
+    );
+}
+
+#[test]
+fn test_milestones_between_dates() {
+    let start = dt(1990, 6, 15, 12, 0, 0);
+    let milestones: Vec<(Milestone, DateTime)> = gigasecond::milestones(start)
+        .between(dt(2020, 1, 1, 0, 0, 0), dt(2030, 1, 1, 0, 0, 0))
+        .collect();
+
+    assert!(!milestones.is_empty());
+    assert!(milestones
+        .iter()
+        .all(|&(_, at)| dt(2020, 1, 1, 0, 0, 0) <= at && at < dt(2030, 1, 1, 0, 0, 0)));
+    // The whole series is skipped to the range, not only its start
+    let months: Vec<i64> = milestones
+        .iter()
+        .filter(|(milestone, _)| milestone.kind == MilestoneKind::HundredMonths)
+        .map(|(milestone, _)| milestone.amount)
+        .collect();
+    assert_eq!(months, [400]);
+}
+
+#[test]
+fn test_milestones_end_at_the_last_date() {
+    let start = dt(9990, 1, 1, 0, 0, 0);
+    let milestones: Vec<(Milestone, DateTime)> = gigasecond::milestones(start).collect();
+
+    assert!(milestones
+        .iter()
+        .all(|(milestone, _)| milestone.kind != MilestoneKind::Gigaseconds));
+    assert_eq!(
+        milestones
+            .last()
+            .map(|(milestone, _)| milestone.to_string()),
+        Some("5,000,000 minutes".to_string())
+    );
+}
+
+fn run_cli(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_gigasecond"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_cli_prints_gigasecond_and_upcoming_milestones() {
+    let output = run_cli(&[
+        "2015-01-24T22:00:00+02:00",
+        "--now",
+        "2026-10-19",
+        "--count",
+        "2",
+    ]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Start:      2015-01-24T22:00:00+02:00\n\
+         Gigasecond: 2046-10-02T23:46:40+02:00\n\
+         \n\
+         Upcoming milestones:\n  \
+         2028-05-17T00:40:00+02:00  7,000,000 minutes\n  \
+         2030-04-11T11:20:00+02:00  8,000,000 minutes\n"
+    );
+}
+
+#[test]
+fn test_cli_accepts_common_formats_and_offsets() {
+    for args in [
+        &["24", "January", "2015", "22:00", "--offset", "+02:00"][..],
+        &["Jan", "24,", "2015", "22:00:00", "--offset", "+0200"],
+        &["2015/01/24 22:00+02"],
+    ] {
+        let output = run_cli(&[args, &["--count", "0"]].concat());
+        assert!(output.status.success(), "{:?}", args);
+        assert!(String::from_utf8(output.stdout)
+            .unwrap()
+            .contains("Gigasecond: 2046-10-02T23:46:40+02:00"));
+    }
+}
+
+#[test]
+fn test_cli_json_output() {
+    let output = run_cli(&[
+        "2015-01-24",
+        "--json",
+        "--now",
+        "2026-10-19",
+        "--count",
+        "1",
+    ]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap().trim(),
+        "{\"start\":\"2015-01-24T00:00:00Z\",\"gigasecond\":\"2046-10-02T01:46:40Z\",\
+         \"milestones\":[{\"milestone\":\"7,000,000 minutes\",\"kind\":\"MillionMinutes\",\
+         \"amount\":7000000,\"unit\":\"minute\",\"at\":\"2028-05-16T02:40:00Z\"}]}"
+    );
+}
+
+#[test]
+fn test_cli_errors_exit_with_nonzero_codes() {
+    for (args, code, message) in [
+        (&["2015-02-30"][..], 2, "February 2015 has no day 30"),
+        (&["24/01/2015"], 2, "'24/01/2015' is not a date"),
+        (&["2015-01-24", "25:00"], 2, "'25:00' is not a time"),
+        (
+            &["2015-01-24", "--offset", "+25:00"],
+            2,
+            "'+25:00' is not an offset",
+        ),
+        (
+            &["2015-01-24Z", "--offset", "+01:00"],
+            2,
+            "--offset was given",
+        ),
+        (&["--json"], 2, "no date given"),
+        (&["9990-01-01"], 1, "past the last date"),
+    ] {
+        let output = run_cli(args);
+        assert_eq!(output.status.code(), Some(code), "{:?}", args);
+        assert!(output.stdout.is_empty());
+        assert!(String::from_utf8(output.stderr).unwrap().contains(message));
+    }
 }
@@ -0,0 +1,460 @@
+//! Importing and replaying text hand histories.
+//!
+//! A history file holds any number of hands, each starting with a `Hand` line.
+//! Blank lines and lines starting with `#` are ignored. Player names are single
+//! words, and cards are written as for `winning_hands`, such as `10H` or `AS`.
+//!
+//! ```text
+//! Hand 17
+//! Seat 1: Alice
+//! Seat 2: Bob
+//! Blinds: Alice 5, Bob 10
+//! Dealt Alice: AS KD
+//! Dealt Bob: 7H 7C
+//! Preflop: Alice raises 30; Bob calls 25
+//! Flop 2C 7D KS: Bob checks; Alice bets 50; Bob calls 50
+//! Turn 3H: Bob checks; Alice checks
+//! River 9S: Bob bets 100; Alice calls 100
+//! Winner: Bob
+//! ```
+//!
+//! The streets come in order, with three board cards on the flop and one each
+//! on the turn and river. An action is one of `folds`, `checks`, `calls <chips>`,
+//! `bets <chips>` or `raises <chips>`, where chips count what the player puts in.
+//! A street without any actions may leave out the colon, as in `Turn 3H`.
+//! Split pots list every winner, separated by commas.
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{Card, HandRank, Holdem, Variant, VariantError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Street {
+    Preflop,
+    Flop,
+    Turn,
+    River,
+}
+
+impl Street {
+    /// The number of cards the street adds to the board.
+    fn board_cards(&self) -> usize {
+        match self {
+            Street::Preflop => 0,
+            Street::Flop => 3,
+            Street::Turn | Street::River => 1,
+        }
+    }
+}
+
+impl fmt::Display for Street {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionKind {
+    Fold,
+    Check,
+    Call(u64),
+    Bet(u64),
+    Raise(u64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Action {
+    pub player: String,
+    pub kind: ActionKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Seat {
+    pub number: usize,
+    pub name: String,
+}
+
+/// One hand as recorded in a history file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandHistory {
+    pub id: String,
+    pub seats: Vec<Seat>,
+    /// The blinds posted, by player name.
+    pub blinds: Vec<(String, u64)>,
+    /// The hole cards dealt, by player name.
+    pub hole_cards: BTreeMap<String, Vec<Card>>,
+    /// The actions on each street reached, in order.
+    pub streets: Vec<(Street, Vec<Action>)>,
+    pub board: Vec<Card>,
+    /// The recorded winners, in the order they were written.
+    pub winners: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseHistoryErrorKind {
+    /// A hand must start with a `Hand <id>` line.
+    MissingHeader,
+    /// The line does not match any part of the format.
+    UnexpectedLine(String),
+    InvalidCard(String),
+    InvalidAmount(String),
+    InvalidAction(String),
+    /// The player is not seated at the table.
+    UnknownPlayer(String),
+    DuplicateSeat(usize),
+    DealtTwice(String),
+    /// The same card appears twice in the hand.
+    DuplicateCard(Card),
+    /// A street came after a later street, or was recorded twice.
+    StreetOutOfOrder(Street),
+    BoardCards {
+        street: Street,
+        expected: usize,
+        found: usize,
+    },
+    /// The player acted after folding.
+    ActionAfterFold(String),
+    /// The hand ended without a `Winner` line.
+    MissingWinner,
+}
+
+/// The reason a hand could not be parsed, and the line number (counting from 1)
+/// in the file where it was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseHistoryError {
+    pub line: usize,
+    pub kind: ParseHistoryErrorKind,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum HistoryError {
+    Parse(ParseHistoryError),
+    /// A player reached showdown without any recorded hole cards.
+    MissingHoleCards(String),
+    /// The cards could not make a hand under the rules of the variant.
+    Variant(VariantError),
+}
+
+impl From<ParseHistoryError> for HistoryError {
+    fn from(err: ParseHistoryError) -> Self {
+        HistoryError::Parse(err)
+    }
+}
+
+impl From<VariantError> for HistoryError {
+    fn from(err: VariantError) -> Self {
+        HistoryError::Variant(err)
+    }
+}
+
+/// The result of replaying one hand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    pub id: String,
+    /// The best hand of each player who reached showdown, in seat order.
+    /// Empty when everyone else folded.
+    pub showdown: Vec<(String, HandRank)>,
+    /// The winners recorded in the history, in seat order.
+    pub recorded: Vec<String>,
+    /// The winners according to the re-evaluated hands, in seat order.
+    pub computed: Vec<String>,
+}
+
+impl Replay {
+    /// Whether the recorded winners agree with the re-evaluated hands.
+    pub fn is_match(&self) -> bool {
+        self.recorded == self.computed
+    }
+}
+
+/// Parse every hand in a history file. A hand which fails to parse does not
+/// stop the hands after it from being read.
+pub fn parse_histories(s: &str) -> Vec<Result<HandHistory, ParseHistoryError>> {
+    let mut blocks: Vec<Vec<(usize, &str)>> = Vec::new();
+    for (i, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with("Hand ") || blocks.is_empty() {
+            blocks.push(Vec::new());
+        }
+        blocks.last_mut().unwrap().push((i + 1, line));
+    }
+    blocks.iter().map(|block| parse_hand(block)).collect()
+}
+
+/// Parse and replay every hand in a history file under hold'em rules,
+/// reporting for each hand whether the recorded winners were right.
+pub fn replay_histories(s: &str) -> Vec<Result<Replay, HistoryError>> {
+    replay_histories_with(&Holdem, s)
+}
+
+/// Parse and replay every hand in a history file under the rules of `variant`.
+pub fn replay_histories_with<V: Variant>(
+    variant: &V,
+    s: &str,
+) -> Vec<Result<Replay, HistoryError>> {
+    parse_histories(s)
+        .into_iter()
+        .map(|history| history?.replay_with(variant))
+        .collect()
+}
+
+impl HandHistory {
+    /// Re-evaluate the showdown under hold'em rules.
+    pub fn replay(&self) -> Result<Replay, HistoryError> {
+        self.replay_with(&Holdem)
+    }
+
+    /// Re-evaluate the showdown under the rules of `variant`, and compare the
+    /// winners with the recorded ones.
+    pub fn replay_with<V: Variant>(&self, variant: &V) -> Result<Replay, HistoryError> {
+        let folded: HashSet<&str> = self
+            .streets
+            .iter()
+            .flat_map(|(_street, actions)| actions)
+            .filter(|action| action.kind == ActionKind::Fold)
+            .map(|action| action.player.as_str())
+            .collect();
+        let live: Vec<&str> = self
+            .seats
+            .iter()
+            .map(|seat| seat.name.as_str())
+            .filter(|name| !folded.contains(name))
+            .collect();
+        let recorded: Vec<String> = self
+            .seats
+            .iter()
+            .filter(|seat| self.winners.contains(&seat.name))
+            .map(|seat| seat.name.clone())
+            .collect();
+
+        if live.len() == 1 {
+            // Everyone else folded, so the hand is won without a showdown
+            return Ok(Replay {
+                id: self.id.clone(),
+                showdown: vec![],
+                recorded,
+                computed: vec![live[0].to_string()],
+            });
+        }
+        let mut showdown = Vec::with_capacity(live.len());
+        for name in live {
+            let hole = self
+                .hole_cards
+                .get(name)
+                .ok_or_else(|| HistoryError::MissingHoleCards(name.to_string()))?;
+            showdown.push((name.to_string(), variant.best_hand(hole, &self.board)?));
+        }
+        let best = showdown
+            .iter()
+            .map(|(_name, rank)| *rank)
+            .max_by(|a, b| variant.compare(a, b))
+            .ok_or(VariantError::NoHands)?;
+        let computed = showdown
+            .iter()
+            .filter(|(_name, rank)| variant.compare(rank, &best) == Ordering::Equal)
+            .map(|(name, _rank)| name.clone())
+            .collect();
+        Ok(Replay {
+            id: self.id.clone(),
+            showdown,
+            recorded,
+            computed,
+        })
+    }
+}
+
+impl FromStr for HandHistory {
+    type Err = ParseHistoryError;
+
+    /// Parse a single hand.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<(usize, &str)> = s
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_i, line)| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+        parse_hand(&lines)
+    }
+}
+
+/// Parse the numbered lines of one hand, with blank lines and comments removed.
+fn parse_hand(lines: &[(usize, &str)]) -> Result<HandHistory, ParseHistoryError> {
+    let err = |line: usize, kind: ParseHistoryErrorKind| ParseHistoryError { line, kind };
+    let (header_line, id) = match lines.first() {
+        Some((n, line)) => match line.strip_prefix("Hand ") {
+            Some(id) => (*n, id.trim().to_string()),
+            None => return Err(err(*n, ParseHistoryErrorKind::MissingHeader)),
+        },
+        None => return Err(err(1, ParseHistoryErrorKind::MissingHeader)),
+    };
+    let mut history = HandHistory {
+        id,
+        seats: Vec::new(),
+        blinds: Vec::new(),
+        hole_cards: BTreeMap::new(),
+        streets: Vec::new(),
+        board: Vec::new(),
+        winners: Vec::new(),
+    };
+    let mut seen_cards = HashSet::new();
+    let mut folded = HashSet::new();
+
+    for &(n, line) in &lines[1..] {
+        let unexpected = || err(n, ParseHistoryErrorKind::UnexpectedLine(line.to_string()));
+        // Streets without any actions may leave out the colon
+        let (head, rest) = line.split_once(':').unwrap_or((line, ""));
+        let rest = rest.trim();
+        let mut words = head.split_whitespace();
+        let keyword = words.next().ok_or_else(unexpected)?;
+        let args: Vec<&str> = words.collect();
+
+        let check_player = |name: &str| {
+            if history.seats.iter().any(|seat| seat.name == name) {
+                Ok(())
+            } else {
+                Err(err(
+                    n,
+                    ParseHistoryErrorKind::UnknownPlayer(name.to_string()),
+                ))
+            }
+        };
+        let mut add_cards = |cards: &str| -> Result<Vec<Card>, ParseHistoryError> {
+            let cards = cards
+                .split_whitespace()
+                .map(|card| {
+                    Card::from_str(card)
+                        .map_err(|_| err(n, ParseHistoryErrorKind::InvalidCard(card.to_string())))
+                })
+                .collect::<Result<Vec<Card>, _>>()?;
+            for card in &cards {
+                if !seen_cards.insert(*card) {
+                    return Err(err(n, ParseHistoryErrorKind::DuplicateCard(*card)));
+                }
+            }
+            Ok(cards)
+        };
+
+        match (keyword, args.as_slice()) {
+            ("Seat", [number]) if !rest.is_empty() => {
+                let number = number.parse().map_err(|_| unexpected())?;
+                if history.seats.iter().any(|seat| seat.number == number) {
+                    return Err(err(n, ParseHistoryErrorKind::DuplicateSeat(number)));
+                }
+                history.seats.push(Seat {
+                    number,
+                    name: rest.to_string(),
+                });
+            }
+            ("Blinds", []) => {
+                for blind in rest.split(',') {
+                    let (name, amount) = blind.trim().split_once(' ').ok_or_else(unexpected)?;
+                    check_player(name)?;
+                    history
+                        .blinds
+                        .push((name.to_string(), parse_amount(amount, n)?));
+                }
+            }
+            ("Dealt", [name]) => {
+                check_player(name)?;
+                if history.hole_cards.contains_key(*name) {
+                    return Err(err(n, ParseHistoryErrorKind::DealtTwice(name.to_string())));
+                }
+                let cards = add_cards(rest)?;
+                history.hole_cards.insert(name.to_string(), cards);
+            }
+            ("Preflop" | "Flop" | "Turn" | "River", board) => {
+                let street = match keyword {
+                    "Preflop" => Street::Preflop,
+                    "Flop" => Street::Flop,
+                    "Turn" => Street::Turn,
+                    _ => Street::River,
+                };
+                let previous = history.streets.last().map(|(street, _actions)| *street);
+                let expected_previous = match street {
+                    Street::Preflop => None,
+                    Street::Flop => Some(Street::Preflop),
+                    Street::Turn => Some(Street::Flop),
+                    Street::River => Some(Street::Turn),
+                };
+                if previous != expected_previous {
+                    return Err(err(n, ParseHistoryErrorKind::StreetOutOfOrder(street)));
+                }
+                if board.len() != street.board_cards() {
+                    return Err(err(
+                        n,
+                        ParseHistoryErrorKind::BoardCards {
+                            street,
+                            expected: street.board_cards(),
+                            found: board.len(),
+                        },
+                    ));
+                }
+                let cards = add_cards(&board.join(" "))?;
+                history.board.extend(cards);
+
+                let mut actions = Vec::new();
+                for action in rest.split(';').map(str::trim).filter(|a| !a.is_empty()) {
+                    let action = parse_action(action, n)?;
+                    check_player(&action.player)?;
+                    if folded.contains(&action.player) {
+                        return Err(err(
+                            n,
+                            ParseHistoryErrorKind::ActionAfterFold(action.player),
+                        ));
+                    }
+                    if action.kind == ActionKind::Fold {
+                        folded.insert(action.player.clone());
+                    }
+                    actions.push(action);
+                }
+                history.streets.push((street, actions));
+            }
+            ("Winner", []) => {
+                for name in rest.split(',').map(str::trim) {
+                    check_player(name)?;
+                    history.winners.push(name.to_string());
+                }
+            }
+            _ => return Err(unexpected()),
+        }
+    }
+    if history.winners.is_empty() {
+        return Err(err(header_line, ParseHistoryErrorKind::MissingWinner));
+    }
+    Ok(history)
+}
+
+fn parse_amount(s: &str, line: usize) -> Result<u64, ParseHistoryError> {
+    s.trim().parse().map_err(|_| ParseHistoryError {
+        line,
+        kind: ParseHistoryErrorKind::InvalidAmount(s.to_string()),
+    })
+}
+
+/// Parse an action such as "Alice raises 30".
+fn parse_action(s: &str, line: usize) -> Result<Action, ParseHistoryError> {
+    let words: Vec<&str> = s.split_whitespace().collect();
+    let kind = match words[1..] {
+        ["folds"] => ActionKind::Fold,
+        ["checks"] => ActionKind::Check,
+        ["calls", amount] => ActionKind::Call(parse_amount(amount, line)?),
+        ["bets", amount] => ActionKind::Bet(parse_amount(amount, line)?),
+        ["raises", amount] => ActionKind::Raise(parse_amount(amount, line)?),
+        _ => {
+            return Err(ParseHistoryError {
+                line,
+                kind: ParseHistoryErrorKind::InvalidAction(s.to_string()),
+            })
+        }
+    };
+    Ok(Action {
+        player: words[0].to_string(),
+        kind,
+    })
+}
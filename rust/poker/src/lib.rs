@@ -1,13 +1,21 @@
-use std::cmp::{max, min};
+use std::cmp::{max, min, Ordering};
 use std::collections::HashMap;
-/// Given a list of poker hands, return a list of those hands which win.
-///
-/// Note the type signature: this function should return _the same_ reference to
-/// the winning hand(s) as were passed in, not reconstructed strings which happen to be equal.
+use std::fmt;
 use std::str::FromStr;
 
-#[derive(Eq, PartialEq, Debug)]
-enum Suit {
+mod history;
+mod pot;
+mod variant;
+
+pub use history::{
+    parse_histories, replay_histories, replay_histories_with, Action, ActionKind, HandHistory,
+    HistoryError, ParseHistoryError, ParseHistoryErrorKind, Replay, Seat, Street,
+};
+pub use pot::{distribute, distribute_with, Distribution, Player, Pot, PotError};
+pub use variant::{Holdem, Omaha, ShortDeck, Variant, VariantError};
+
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub enum Suit {
     Spades,
     Hearts,
     Diamonds,
@@ -15,7 +23,7 @@ enum Suit {
 }
 
 #[derive(Eq, PartialEq, PartialOrd, Ord, Hash, Copy, Clone, Debug)]
-enum Rank {
+pub enum Rank {
     Two = 2,
     Three = 3,
     Four = 4,
@@ -32,7 +40,7 @@ enum Rank {
 }
 
 #[derive(Debug, PartialEq, Eq)]
-struct ParseRankError;
+pub struct ParseRankError;
 
 impl FromStr for Rank {
     type Err = ParseRankError;
@@ -57,8 +65,25 @@ impl FromStr for Rank {
     }
 }
 
+impl Rank {
+    /// The name of the rank in plural, as in "a pair of Sixes".
+    fn plural(&self) -> String {
+        match self {
+            Rank::Six => "Sixes".to_string(),
+            rank => format!("{}s", rank),
+        }
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The derived Debug output is the name of the rank
+        write!(f, "{:?}", self)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
-struct ParseSuitError;
+pub struct ParseSuitError;
 
 impl FromStr for Suit {
     type Err = ParseSuitError;
@@ -74,19 +99,22 @@ impl FromStr for Suit {
     }
 }
 
-#[derive(PartialEq, Eq)]
-struct Card {
-    rank: Rank,
-    suit: Suit,
+#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+pub struct Card {
+    pub rank: Rank,
+    pub suit: Suit,
 }
 
 #[derive(Debug, PartialEq, Eq)]
-struct ParseCardError;
+pub struct ParseCardError;
 
 impl FromStr for Card {
     type Err = ParseCardError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() < 2 || !s.is_char_boundary(s.len() - 1) {
+            return Err(ParseCardError);
+        }
         let rank = match Rank::from_str(&s[0..s.len() - 1]) {
             Ok(rank) => rank,
             Err(..) => return Err(ParseCardError),
@@ -95,11 +123,7 @@ impl FromStr for Card {
             Ok(suit) => suit,
             Err(..) => return Err(ParseCardError),
         };
-        // Ok(Card(rank: rank, suit: suit))
-        Ok(Card {
-            rank: rank,
-            suit: suit,
-        })
+        Ok(Card { rank, suit })
     }
 }
 
@@ -109,7 +133,7 @@ struct Hand {
 }
 
 #[derive(Eq, PartialEq, PartialOrd, Ord, Copy, Clone, Debug)]
-enum HandRank {
+pub enum HandRank {
     // Ordering is derived. Higher ordering is a better hand.
     HighCard(Rank, Rank, Rank, Rank, Rank),
     OnePair(Rank, Rank, Rank, Rank),
@@ -122,14 +146,126 @@ enum HandRank {
     StraightFlush(Rank),
 }
 
+impl HandRank {
+    /// Position of the hand category in the standard ordering, from 0 (high card)
+    /// to 8 (straight flush).
+    pub(crate) fn category(&self) -> u8 {
+        match self {
+            HandRank::HighCard(..) => 0,
+            HandRank::OnePair(..) => 1,
+            HandRank::TwoPair(..) => 2,
+            HandRank::ThreeOfAKind(..) => 3,
+            HandRank::Straight(..) => 4,
+            HandRank::Flush(..) => 5,
+            HandRank::FullHouse(..) => 6,
+            HandRank::FourOfAKind(..) => 7,
+            HandRank::StraightFlush(..) => 8,
+        }
+    }
+
+    /// Name of the hand category, in lower case.
+    fn category_name(&self) -> &'static str {
+        match self {
+            HandRank::HighCard(..) => "high card",
+            HandRank::OnePair(..) => "pair",
+            HandRank::TwoPair(..) => "two pair",
+            HandRank::ThreeOfAKind(..) => "three of a kind",
+            HandRank::Straight(..) => "straight",
+            HandRank::Flush(..) => "flush",
+            HandRank::FullHouse(..) => "full house",
+            HandRank::FourOfAKind(..) => "four of a kind",
+            HandRank::StraightFlush(..) => "straight flush",
+        }
+    }
+
+    /// Split the ranks of the hand into those which make up the category,
+    /// such as the two pairs of a two pair, and the kickers that break ties.
+    fn split_kickers(&self) -> (Vec<Rank>, Vec<Rank>) {
+        match *self {
+            HandRank::HighCard(a, b, c, d, e) | HandRank::Flush(a, b, c, d, e) => {
+                (vec![a], vec![b, c, d, e])
+            }
+            HandRank::OnePair(pair, a, b, c) => (vec![pair], vec![a, b, c]),
+            HandRank::TwoPair(high, low, kicker) => (vec![high, low], vec![kicker]),
+            HandRank::ThreeOfAKind(three, a, b) => (vec![three], vec![a, b]),
+            HandRank::Straight(high) | HandRank::StraightFlush(high) => (vec![high], vec![]),
+            HandRank::FullHouse(three, pair) => (vec![three, pair], vec![]),
+            HandRank::FourOfAKind(four, kicker) => (vec![four], vec![kicker]),
+        }
+    }
+}
+
+impl fmt::Display for HandRank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HandRank::HighCard(high, ..) => write!(f, "High card, {}", high),
+            HandRank::OnePair(pair, ..) => write!(f, "Pair of {}", pair.plural()),
+            HandRank::TwoPair(high, low, _) => {
+                write!(f, "Two pair, {} and {}", high.plural(), low.plural())
+            }
+            HandRank::ThreeOfAKind(three, ..) => write!(f, "Three of a kind, {}", three.plural()),
+            HandRank::Straight(high) => write!(f, "Straight, {} high", high),
+            HandRank::Flush(high, ..) => write!(f, "Flush, {} high", high),
+            HandRank::FullHouse(three, pair) => {
+                write!(f, "Full house, {} over {}", three.plural(), pair.plural())
+            }
+            HandRank::FourOfAKind(four, _) => write!(f, "Four of a kind, {}", four.plural()),
+            HandRank::StraightFlush(Rank::Ace) => write!(f, "Royal flush"),
+            HandRank::StraightFlush(high) => write!(f, "Straight flush, {} high", high),
+        }
+    }
+}
+
+/// Explain the outcome between two hands in words, such as
+/// "Pair of Jacks beats Pair of Tens" or "Same two pair, won on Queen kicker".
+///
+/// The hands may be given in either order.
+pub fn explain(a: &HandRank, b: &HandRank) -> String {
+    explain_ordered(a, b, a.cmp(b))
+}
+
+/// Explain the outcome between two hands, where `ordering` is how `a`
+/// compares to `b` under the rules of the game.
+pub(crate) fn explain_ordered(a: &HandRank, b: &HandRank, ordering: Ordering) -> String {
+    let (winner, loser) = match ordering {
+        Ordering::Equal => return format!("Tie, both have {}", a),
+        Ordering::Greater => (a, b),
+        Ordering::Less => (b, a),
+    };
+    let (winner_ranks, winner_kickers) = winner.split_kickers();
+    let (loser_ranks, loser_kickers) = loser.split_kickers();
+    if winner.category() == loser.category() && winner_ranks == loser_ranks {
+        // The hands only differ by kickers, so name the first one that decided it
+        let kicker = winner_kickers
+            .iter()
+            .zip(&loser_kickers)
+            .find(|(w, l)| w != l)
+            .map(|(w, _l)| *w);
+        if let Some(kicker) = kicker {
+            return format!("Same {}, won on {} kicker", winner.category_name(), kicker);
+        }
+    }
+    format!("{} beats {}", winner, loser)
+}
+
+/// The ranks which, together with an ace counting low, make the lowest straight
+/// in a standard 52-card deck.
+const WHEEL: [Rank; 4] = [Rank::Two, Rank::Three, Rank::Four, Rank::Five];
+
 impl Hand {
     fn new(mut cards: Vec<Card>) -> Self {
         // Sort cards lowest to highest rank
-        cards.sort_by(|card1, card2| card1.rank.cmp(&card2.rank));
+        cards.sort_by_key(|card| card.rank);
         Self { cards }
     }
 
-    fn rank(self) -> HandRank {
+    fn rank(&self) -> HandRank {
+        self.rank_with_wheel(&WHEEL)
+    }
+
+    /// Rank the hand, where `wheel` holds the four lowest ranks of the deck,
+    /// which make a straight when an ace is played low.
+    fn rank_with_wheel(&self, wheel: &[Rank; 4]) -> HandRank {
         // Count the number of occurrances for each rank
         let mut rank_counts: HashMap<Rank, u8> = HashMap::new();
         for card in &self.cards {
@@ -138,9 +274,9 @@ impl Hand {
                 .and_modify(|count| *count += 1)
                 .or_insert(1);
         }
-        // Sort rank counts in descending order
+        // Sort rank counts in descending order, breaking ties on the higher rank
         let mut rank_counts_sorted: Vec<(&Rank, &u8)> = rank_counts.iter().collect();
-        rank_counts_sorted.sort_by(|a, b| b.1.cmp(a.1));
+        rank_counts_sorted.sort_by(|a, b| b.1.cmp(a.1).then(b.0.cmp(a.0)));
         if *rank_counts_sorted[0].1 == 4 {
             return HandRank::FourOfAKind(*rank_counts_sorted[0].0, *rank_counts_sorted[1].0);
         } else if *rank_counts_sorted[0].1 == 3 {
@@ -170,57 +306,23 @@ impl Hand {
         }
 
         let flush = self.cards.iter().all(|c| c.suit == self.cards[0].suit);
-        match self.cards[..] {
-            // Straight flush with `a` as highest ranking card
-            [Card { rank: a, .. }, Card { rank: b, .. }, Card { rank: c, .. }, Card { rank: d, .. }, Card { rank: e, .. }]
-                if (a as u32 == b as u32 - 1)
-                    && (b as u32 == c as u32 - 1)
-                    && (c as u32 == d as u32 - 1)
-                    && (d as u32 == e as u32 - 1) =>
-            {
-                if flush {
-                    return HandRank::StraightFlush(e);
-                } else {
-                    return HandRank::Straight(e);
-                }
-            }
-            [Card {
-                rank: Rank::Two, ..
-            }, Card {
-                rank: Rank::Three, ..
-            }, Card {
-                rank: Rank::Four, ..
-            }, Card {
-                rank: Rank::Five, ..
-            }, Card {
-                rank: Rank::Ace, ..
-            }] => {
-                if flush {
-                    // Special case: Five-high (ace as 1) straight flush
-                    return HandRank::StraightFlush(Rank::Five);
-                } else {
-                    // Special case: Five-high (ace as 1) straight
-                    return HandRank::Straight(Rank::Five);
-                }
-            }
-            _ => {}
-        }
-        if flush {
-            return HandRank::Flush(
-                self.cards[0].rank,
-                self.cards[1].rank,
-                self.cards[2].rank,
-                self.cards[3].rank,
-                self.cards[4].rank,
-            );
+        let ranks: Vec<Rank> = self.cards.iter().map(|c| c.rank).collect();
+        // Highest ranking card of the straight, if there is one
+        let straight = if ranks.windows(2).all(|w| w[0] as u32 + 1 == w[1] as u32) {
+            Some(ranks[4])
+        } else if ranks[..4] == wheel[..] && ranks[4] == Rank::Ace {
+            // Special case: ace counts low, so the straight tops out at the last wheel card
+            Some(wheel[3])
+        } else {
+            None
+        };
+        match (straight, flush) {
+            (Some(high), true) => HandRank::StraightFlush(high),
+            (Some(high), false) => HandRank::Straight(high),
+            // Kickers are compared from the highest card down
+            (None, true) => HandRank::Flush(ranks[4], ranks[3], ranks[2], ranks[1], ranks[0]),
+            (None, false) => HandRank::HighCard(ranks[4], ranks[3], ranks[2], ranks[1], ranks[0]),
         }
-        return HandRank::HighCard(
-            self.cards[0].rank,
-            self.cards[1].rank,
-            self.cards[2].rank,
-            self.cards[3].rank,
-            self.cards[4].rank,
-        );
     }
 }
 
@@ -231,30 +333,32 @@ impl FromStr for Hand {
     type Err = ParseHandError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.split(" ").map(Card::from_str).collect() {
+        match s.split(' ').map(Card::from_str).collect() {
             Ok(cards) => Ok(Hand::new(cards)),
             Err(..) => Err(ParseHandError),
         }
     }
 }
 
+/// Given a list of poker hands, return a list of those hands which win.
+///
+/// Note the type signature: this function should return _the same_ reference to
+/// the winning hand(s) as were passed in, not reconstructed strings which happen to be equal.
 pub fn winning_hands<'a>(hands: &[&'a str]) -> Vec<&'a str> {
     let mb_parsed_hands: Result<Vec<Hand>, _> =
-        hands.iter().map(|hand| Hand::from_str(&hand)).collect();
+        hands.iter().map(|hand| Hand::from_str(hand)).collect();
     let parsed_hands: Vec<Hand> = mb_parsed_hands.unwrap();
     let mut ranked_hands: Vec<(HandRank, &&str)> = parsed_hands
-        .into_iter()
+        .iter()
         .map(|hand| hand.rank())
-        .zip(hands.into_iter())
+        .zip(hands.iter())
         .collect();
-    ranked_hands.sort_by(|(rank1, _hand1), (rank2, _hand2)| rank2.cmp(&rank1));
-    println!("ranked hands: {:?}", ranked_hands);
-    let best_rank = ranked_hands[0].0.clone();
+    ranked_hands.sort_by(|(rank1, _hand1), (rank2, _hand2)| rank2.cmp(rank1));
+    let best_rank = ranked_hands[0].0;
     let best_hands: Vec<&str> = ranked_hands
         .into_iter()
         .take_while(|(rank, _hand)| rank == &best_rank)
         .map(|(_rank, hand)| *hand)
         .collect();
-    println!("hands: {:?} of which best: {:?}", hands, best_hands);
     best_hands
 }
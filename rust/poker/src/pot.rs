@@ -0,0 +1,150 @@
+//! Dividing the chips once the hands are known, with side pots for players
+//! who went all in and split pots for tied hands.
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::{HandRank, Holdem, Variant};
+
+/// A player's part in a finished hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Player {
+    pub seat: usize,
+    /// The total number of chips the player put into the pot over all streets.
+    pub contribution: u64,
+    pub all_in: bool,
+    /// The player's hand at showdown, or `None` if they folded.
+    pub hand: Option<HandRank>,
+}
+
+/// One pot, either the main pot or a side pot, and how it was awarded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pot {
+    pub amount: u64,
+    /// The seats which had a claim on the pot, in seat order.
+    pub eligible: Vec<usize>,
+    /// The best hand among the eligible players.
+    pub winning_hand: HandRank,
+    /// The chips each winner received, in the order the odd chips were dealt.
+    pub shares: Vec<(usize, u64)>,
+}
+
+/// The complete breakdown of how the chips were divided.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Distribution {
+    /// The main pot first, followed by the side pots.
+    pub pots: Vec<Pot>,
+    /// The chips won by each seat over all pots.
+    pub payouts: BTreeMap<usize, u64>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PotError {
+    /// Every player folded, so no one can win the pot.
+    NoLivePlayers,
+    /// Two players were given the same seat.
+    DuplicateSeat(usize),
+    /// A player who is neither folded nor all in put in less than another
+    /// player, so they have not called the bet.
+    ShortContribution(usize),
+}
+
+/// Divide the chips among the players under standard hand ordering.
+///
+/// `button` is the seat of the dealer button. When a pot is split, odd chips
+/// go one at a time to the winners in seat order, starting left of the button.
+pub fn distribute(players: &[Player], button: usize) -> Result<Distribution, PotError> {
+    distribute_with(&Holdem, players, button)
+}
+
+/// Divide the chips among the players, ordering hands by the rules of `variant`.
+pub fn distribute_with<V: Variant>(
+    variant: &V,
+    players: &[Player],
+    button: usize,
+) -> Result<Distribution, PotError> {
+    let mut seats = HashSet::new();
+    for player in players {
+        if !seats.insert(player.seat) {
+            return Err(PotError::DuplicateSeat(player.seat));
+        }
+    }
+    let mut live: Vec<(&Player, HandRank)> = players
+        .iter()
+        .filter_map(|player| player.hand.map(|hand| (player, hand)))
+        .collect();
+    // Seat order, starting with the first seat left of the button
+    live.sort_by_key(|(player, _hand)| (player.seat <= button, player.seat));
+    let top = match live.iter().map(|(player, _hand)| player.contribution).max() {
+        Some(top) => top,
+        None => return Err(PotError::NoLivePlayers),
+    };
+    if let Some((short, _hand)) = live
+        .iter()
+        .find(|(player, _hand)| !player.all_in && player.contribution < top)
+    {
+        return Err(PotError::ShortContribution(short.seat));
+    }
+
+    // Each distinct contribution of a live player caps one pot
+    let mut levels: Vec<u64> = live
+        .iter()
+        .map(|(player, _hand)| player.contribution)
+        .collect();
+    levels.sort_unstable();
+    levels.dedup();
+
+    let mut pots: Vec<Pot> = Vec::new();
+    let mut payouts = BTreeMap::new();
+    let mut previous = 0;
+    for level in levels {
+        let mut amount: u64 = players
+            .iter()
+            .map(|player| player.contribution.min(level) - player.contribution.min(previous))
+            .sum();
+        if level == top {
+            // Chips folded players put in above every live player go to the last pot
+            amount += players
+                .iter()
+                .map(|player| player.contribution.saturating_sub(top))
+                .sum::<u64>();
+        }
+        previous = level;
+        if amount == 0 {
+            continue;
+        }
+        let eligible: Vec<(&Player, HandRank)> = live
+            .iter()
+            .filter(|(player, _hand)| player.contribution >= level)
+            .copied()
+            .collect();
+        let winning_hand = eligible
+            .iter()
+            .map(|(_player, hand)| *hand)
+            .max_by(|a, b| variant.compare(a, b))
+            .expect("the top level always has an eligible player");
+        let winners: Vec<usize> = eligible
+            .iter()
+            .filter(|(_player, hand)| variant.compare(hand, &winning_hand) == Ordering::Equal)
+            .map(|(player, _hand)| player.seat)
+            .collect();
+        let share = amount / winners.len() as u64;
+        let odd_chips = (amount % winners.len() as u64) as usize;
+        let shares: Vec<(usize, u64)> = winners
+            .iter()
+            .enumerate()
+            .map(|(i, seat)| (*seat, share + u64::from(i < odd_chips)))
+            .collect();
+        for (seat, chips) in &shares {
+            *payouts.entry(*seat).or_insert(0) += chips;
+        }
+        let mut eligible: Vec<usize> = eligible.iter().map(|(player, _hand)| player.seat).collect();
+        eligible.sort_unstable();
+        pots.push(Pot {
+            amount,
+            eligible,
+            winning_hand,
+            shares,
+        });
+    }
+    Ok(Distribution { pots, payouts })
+}
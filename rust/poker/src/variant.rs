@@ -0,0 +1,248 @@
+//! Rules for community card variants, where each player builds the best
+//! five-card hand out of their own hole cards and the shared board.
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use crate::{explain_ordered, Card, Hand, HandRank, ParseCardError, Rank};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum VariantError {
+    /// A card could not be parsed.
+    ParseCard(ParseCardError),
+    /// A player holds a different number of hole cards than the variant deals.
+    HoleCards { expected: usize, found: usize },
+    /// The board must hold between three (the flop) and five (the river) cards.
+    BoardCards(usize),
+    /// The card is not part of the deck the variant is played with.
+    NotInDeck(Card),
+    /// The same card was dealt twice.
+    DuplicateCard(Card),
+    /// There are no hands to compare.
+    NoHands,
+}
+
+impl From<ParseCardError> for VariantError {
+    fn from(err: ParseCardError) -> Self {
+        VariantError::ParseCard(err)
+    }
+}
+
+/// Rules for how hands are constructed and ordered in a poker variant.
+///
+/// Implementors decide which five-card hands a player may form and how they
+/// are ranked; validation and showdown are provided on top of that.
+pub trait Variant {
+    /// The number of hole cards dealt to each player.
+    fn hole_cards(&self) -> usize;
+
+    /// Every five-card hand a player may form from their hole cards and the board.
+    fn candidate_hands(&self, hole: &[Card], board: &[Card]) -> Vec<[Card; 5]>;
+
+    /// Whether the card is part of the deck this variant is played with.
+    fn in_deck(&self, _card: &Card) -> bool {
+        true
+    }
+
+    /// Rank a five-card hand.
+    fn rank(&self, cards: &[Card; 5]) -> HandRank {
+        Hand::new(cards.to_vec()).rank()
+    }
+
+    /// Order two ranked hands. Higher ordering is a better hand.
+    fn compare(&self, a: &HandRank, b: &HandRank) -> Ordering {
+        a.cmp(b)
+    }
+
+    /// Explain the outcome between two hands in words, under the ordering of
+    /// this variant. The hands may be given in either order.
+    fn explain(&self, a: &HandRank, b: &HandRank) -> String {
+        explain_ordered(a, b, self.compare(a, b))
+    }
+
+    /// The best hand a player can make with their hole cards and the board.
+    fn best_hand(&self, hole: &[Card], board: &[Card]) -> Result<HandRank, VariantError> {
+        if hole.len() != self.hole_cards() {
+            return Err(VariantError::HoleCards {
+                expected: self.hole_cards(),
+                found: hole.len(),
+            });
+        }
+        if !(3..=5).contains(&board.len()) {
+            return Err(VariantError::BoardCards(board.len()));
+        }
+        let mut seen = HashSet::new();
+        for card in hole.iter().chain(board) {
+            if !self.in_deck(card) {
+                return Err(VariantError::NotInDeck(*card));
+            }
+            if !seen.insert(*card) {
+                return Err(VariantError::DuplicateCard(*card));
+            }
+        }
+        let best = self
+            .candidate_hands(hole, board)
+            .iter()
+            .map(|cards| self.rank(cards))
+            .max_by(|a, b| self.compare(a, b));
+        // Every variant can make at least one hand from a valid deal
+        Ok(best.expect("no five-card hand can be formed"))
+    }
+
+    /// Given the board and each player's hole cards, return the hole cards
+    /// of the player(s) who win the showdown.
+    ///
+    /// Like `winning_hands`, this returns _the same_ references as were passed in.
+    fn winning_hands<'a>(
+        &self,
+        board: &str,
+        holes: &[&'a str],
+    ) -> Result<Vec<&'a str>, VariantError> {
+        let board = parse_cards(board)?;
+        let mut seen: HashSet<Card> = board.iter().copied().collect();
+        let mut ranked_hands = Vec::with_capacity(holes.len());
+        for hole in holes {
+            let hole_cards = parse_cards(hole)?;
+            // Cards may not be shared between players either
+            for card in &hole_cards {
+                if !seen.insert(*card) {
+                    return Err(VariantError::DuplicateCard(*card));
+                }
+            }
+            ranked_hands.push((self.best_hand(&hole_cards, &board)?, *hole));
+        }
+        let best_rank = ranked_hands
+            .iter()
+            .map(|(rank, _hole)| *rank)
+            .max_by(|a, b| self.compare(a, b))
+            .ok_or(VariantError::NoHands)?;
+        Ok(ranked_hands
+            .into_iter()
+            .filter(|(rank, _hole)| self.compare(rank, &best_rank) == Ordering::Equal)
+            .map(|(_rank, hole)| hole)
+            .collect())
+    }
+}
+
+/// Parse space separated cards, such as "AS KD 10H".
+fn parse_cards(s: &str) -> Result<Vec<Card>, ParseCardError> {
+    s.split_whitespace().map(Card::from_str).collect()
+}
+
+/// All ways of choosing five cards out of `cards`, keeping their order.
+fn five_card_hands(cards: &[Card]) -> Vec<[Card; 5]> {
+    combinations(cards, 5)
+        .into_iter()
+        .map(|combo| {
+            combo
+                .try_into()
+                .expect("combinations of five have five cards")
+        })
+        .collect()
+}
+
+/// All ways of choosing `k` cards out of `cards`, keeping their order.
+fn combinations(cards: &[Card], k: usize) -> Vec<Vec<Card>> {
+    if k == 0 {
+        return vec![vec![]];
+    }
+    if cards.len() < k {
+        return vec![];
+    }
+    let (first, rest) = (cards[0], &cards[1..]);
+    let mut combos: Vec<Vec<Card>> = combinations(rest, k - 1)
+        .into_iter()
+        .map(|mut combo| {
+            combo.insert(0, first);
+            combo
+        })
+        .collect();
+    combos.extend(combinations(rest, k));
+    combos
+}
+
+/// Texas hold'em: two hole cards, and any five of the seven available cards
+/// make the hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Holdem;
+
+impl Variant for Holdem {
+    fn hole_cards(&self) -> usize {
+        2
+    }
+
+    fn candidate_hands(&self, hole: &[Card], board: &[Card]) -> Vec<[Card; 5]> {
+        let cards: Vec<Card> = hole.iter().chain(board).copied().collect();
+        five_card_hands(&cards)
+    }
+}
+
+/// Omaha: the hand must use exactly two hole cards and exactly three board cards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Omaha {
+    /// Four hole cards per player.
+    FourCard,
+    /// Five hole cards per player.
+    FiveCard,
+}
+
+impl Variant for Omaha {
+    fn hole_cards(&self) -> usize {
+        match self {
+            Omaha::FourCard => 4,
+            Omaha::FiveCard => 5,
+        }
+    }
+
+    fn candidate_hands(&self, hole: &[Card], board: &[Card]) -> Vec<[Card; 5]> {
+        let board_combos = combinations(board, 3);
+        combinations(hole, 2)
+            .into_iter()
+            .flat_map(|hole_combo| {
+                board_combos.iter().map(move |board_combo| {
+                    [&hole_combo[..], board_combo]
+                        .concat()
+                        .try_into()
+                        .expect("two hole cards and three board cards make five")
+                })
+            })
+            .collect()
+    }
+}
+
+/// Short deck (six plus) hold'em: played with the 36 cards from six to ace.
+/// The ace also plays low below the six, so A-6-7-8-9 is the lowest straight,
+/// and as flushes are rarer with fewer cards, a flush beats a full house.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ShortDeck;
+
+/// The ranks which make the lowest straight together with a low ace in a short deck.
+const SHORT_DECK_WHEEL: [Rank; 4] = [Rank::Six, Rank::Seven, Rank::Eight, Rank::Nine];
+
+impl Variant for ShortDeck {
+    fn hole_cards(&self) -> usize {
+        Holdem.hole_cards()
+    }
+
+    fn candidate_hands(&self, hole: &[Card], board: &[Card]) -> Vec<[Card; 5]> {
+        Holdem.candidate_hands(hole, board)
+    }
+
+    fn in_deck(&self, card: &Card) -> bool {
+        card.rank >= Rank::Six
+    }
+
+    fn rank(&self, cards: &[Card; 5]) -> HandRank {
+        Hand::new(cards.to_vec()).rank_with_wheel(&SHORT_DECK_WHEEL)
+    }
+
+    fn compare(&self, a: &HandRank, b: &HandRank) -> Ordering {
+        // Swap flush and full house, leaving the other categories in place
+        let category = |rank: &HandRank| match rank.category() {
+            5 => 6,
+            6 => 5,
+            category => category,
+        };
+        category(a).cmp(&category(b)).then_with(|| a.cmp(b))
+    }
+}
@@ -1,4 +1,8 @@
-use poker::winning_hands;
+use poker::{
+    distribute, explain, parse_histories, replay_histories, winning_hands, Card, HandHistory,
+    HandRank, HistoryError, Holdem, Omaha, ParseHistoryError, ParseHistoryErrorKind, Player,
+    PotError, Rank, ShortDeck, Street, Suit, Variant, VariantError,
+};
 use std::collections::HashSet;
 
 fn hs_from<'a>(input: &[&'a str]) -> HashSet<&'a str> {
@@ -262,3 +266,418 @@ fn test_straight_flush_scoring() {
     // even though an ace is usually high, a 5-high straight flush is the lowest-scoring straight flush
     test(&["2H 3H 4H 5H 6H", "4D AD 3D 2D 5D"], &["2H 3H 4H 5H 6H"])
 }
+
+#[test]
+fn test_high_card_kickers_compare_from_the_top() {
+    test(&["2S 3H 4D 6C KS", "3S 4H 5D 7C QS"], &["2S 3H 4D 6C KS"])
+}
+
+#[test]
+fn test_flush_kickers_compare_from_the_top() {
+    test(&["2H 4H 5H 6H KH", "3D 4D 5D 7D QD"], &["2H 4H 5H 6H KH"])
+}
+
+#[test]
+fn test_one_pair_kickers_compare_from_the_top() {
+    // The kickers must not depend on the order the ranks were counted in
+    for _ in 0..20 {
+        test(&["4S 4H 2D 3C KS", "4D 4C 5H 6S QD"], &["4S 4H 2D 3C KS"])
+    }
+}
+
+#[test]
+fn test_holdem_uses_best_five_of_seven() {
+    // Both players play the board straight, but the ace of hearts completes a flush
+    let winners = Holdem
+        .winning_hands("2H 3H 4H 5C 6D", &["AH 9H", "KS KD"])
+        .unwrap();
+    assert_eq!(winners, vec!["AH 9H"])
+}
+
+#[test]
+fn test_holdem_board_plays_for_a_split() {
+    let winners = Holdem
+        .winning_hands("10S JS QD KC AH", &["2C 3D", "4H 5S"])
+        .unwrap();
+    assert_eq!(winners, vec!["2C 3D", "4H 5S"])
+}
+
+#[test]
+fn test_omaha_requires_exactly_two_hole_cards() {
+    // Four hearts in the hole make no flush without three hearts on the board
+    let hole = "AH KH QH JH".split(' ').map(|c| c.parse().unwrap());
+    let board = "2H 7C 8D 9S 3S".split(' ').map(|c| c.parse().unwrap());
+    let rank = Omaha::FourCard
+        .best_hand(&hole.collect::<Vec<_>>(), &board.collect::<Vec<_>>())
+        .unwrap();
+    assert_eq!(
+        rank,
+        HandRank::HighCard(Rank::Ace, Rank::King, Rank::Nine, Rank::Eight, Rank::Seven)
+    )
+}
+
+#[test]
+fn test_omaha_requires_exactly_three_board_cards() {
+    // The board holds four spades, but only two hole spades may join three of them
+    let winners = Omaha::FourCard
+        .winning_hands("2S 5S 8S JS KD", &["AS 3C 4D 6H", "QH QD 9C 10C"])
+        .unwrap();
+    assert_eq!(winners, vec!["QH QD 9C 10C"])
+}
+
+#[test]
+fn test_five_card_omaha() {
+    let winners = Omaha::FiveCard
+        .winning_hands("2S 5S 8S JS KD", &["AS 3S 4D 6H 7C", "QH QD 9C 10C 2D"])
+        .unwrap();
+    assert_eq!(winners, vec!["AS 3S 4D 6H 7C"])
+}
+
+#[test]
+fn test_omaha_rejects_wrong_number_of_hole_cards() {
+    assert_eq!(
+        Omaha::FourCard.winning_hands("2S 5S 8S JS KD", &["AS 3C 4D"]),
+        Err(VariantError::HoleCards {
+            expected: 4,
+            found: 3
+        })
+    )
+}
+
+#[test]
+fn test_variants_reject_duplicate_cards() {
+    assert_eq!(
+        Holdem.winning_hands("2S 5S 8S JS KD", &["AS 3C", "AS 4D"]),
+        Err(VariantError::DuplicateCard(Card {
+            rank: Rank::Ace,
+            suit: Suit::Spades
+        }))
+    )
+}
+
+#[test]
+fn test_short_deck_has_no_cards_below_six() {
+    assert_eq!(
+        ShortDeck.winning_hands("6S 7D 8H JS KD", &["AS 5C"]),
+        Err(VariantError::NotInDeck(Card {
+            rank: Rank::Five,
+            suit: Suit::Clubs
+        }))
+    )
+}
+
+#[test]
+fn test_short_deck_flush_beats_full_house() {
+    let winners = ShortDeck
+        .winning_hands("6H 6S 9H JH KD", &["AH 7H", "6D 9S"])
+        .unwrap();
+    assert_eq!(winners, vec!["AH 7H"])
+}
+
+#[test]
+fn test_short_deck_ace_can_start_a_straight_below_six() {
+    let winners = ShortDeck
+        .winning_hands("6S 7D 8H QS KD", &["AC 9C", "QH JC"])
+        .unwrap();
+    assert_eq!(winners, vec!["AC 9C"]);
+    let hole = ["AC".parse().unwrap(), "9C".parse().unwrap()];
+    let board: Vec<Card> = "6S 7D 8H QS KD"
+        .split(' ')
+        .map(|c| c.parse().unwrap())
+        .collect();
+    assert_eq!(
+        ShortDeck.best_hand(&hole, &board),
+        Ok(HandRank::Straight(Rank::Nine))
+    )
+}
+
+#[test]
+fn test_display_hand_ranks() {
+    assert_eq!(
+        HandRank::FullHouse(Rank::King, Rank::Three).to_string(),
+        "Full house, Kings over Threes"
+    );
+    assert_eq!(
+        HandRank::Straight(Rank::Five).to_string(),
+        "Straight, Five high"
+    );
+    assert_eq!(
+        HandRank::OnePair(Rank::Six, Rank::Ace, Rank::Nine, Rank::Two).to_string(),
+        "Pair of Sixes"
+    );
+    assert_eq!(
+        HandRank::StraightFlush(Rank::Ace).to_string(),
+        "Royal flush"
+    );
+}
+
+#[test]
+fn test_explain_different_pairs() {
+    let jacks = HandRank::OnePair(Rank::Jack, Rank::Four, Rank::Three, Rank::Two);
+    let tens = HandRank::OnePair(Rank::Ten, Rank::Ace, Rank::King, Rank::Queen);
+    assert_eq!(explain(&jacks, &tens), "Pair of Jacks beats Pair of Tens");
+    assert_eq!(explain(&tens, &jacks), "Pair of Jacks beats Pair of Tens");
+}
+
+#[test]
+fn test_explain_kicker() {
+    let queen = HandRank::TwoPair(Rank::King, Rank::Five, Rank::Queen);
+    let jack = HandRank::TwoPair(Rank::King, Rank::Five, Rank::Jack);
+    assert_eq!(explain(&queen, &jack), "Same two pair, won on Queen kicker");
+}
+
+#[test]
+fn test_explain_tie() {
+    let straight = HandRank::Straight(Rank::Ten);
+    assert_eq!(
+        explain(&straight, &straight),
+        "Tie, both have Straight, Ten high"
+    );
+}
+
+#[test]
+fn test_explain_uses_variant_ordering() {
+    let flush = HandRank::Flush(Rank::Ace, Rank::Jack, Rank::Nine, Rank::Seven, Rank::Six);
+    let full_house = HandRank::FullHouse(Rank::Six, Rank::Nine);
+    assert_eq!(
+        ShortDeck.explain(&full_house, &flush),
+        "Flush, Ace high beats Full house, Sixes over Nines"
+    );
+}
+
+fn player(seat: usize, contribution: u64, all_in: bool, hand: Option<HandRank>) -> Player {
+    Player {
+        seat,
+        contribution,
+        all_in,
+        hand,
+    }
+}
+
+#[test]
+fn test_single_winner_takes_the_pot() {
+    let players = [
+        player(1, 100, false, Some(HandRank::Straight(Rank::Nine))),
+        player(2, 100, false, Some(HandRank::Straight(Rank::Eight))),
+        player(3, 40, false, None),
+    ];
+    let distribution = distribute(&players, 0).unwrap();
+    assert_eq!(distribution.pots.len(), 1);
+    assert_eq!(distribution.pots[0].amount, 240);
+    assert_eq!(distribution.pots[0].eligible, vec![1, 2]);
+    assert_eq!(
+        distribution.payouts.into_iter().collect::<Vec<_>>(),
+        vec![(1, 240)]
+    );
+}
+
+#[test]
+fn test_short_all_in_only_wins_the_main_pot() {
+    let players = [
+        player(
+            1,
+            50,
+            true,
+            Some(HandRank::FourOfAKind(Rank::Two, Rank::Ace)),
+        ),
+        player(2, 200, false, Some(HandRank::Straight(Rank::Nine))),
+        player(3, 200, false, Some(HandRank::Straight(Rank::Eight))),
+    ];
+    let distribution = distribute(&players, 0).unwrap();
+    let pots: Vec<(u64, Vec<usize>)> = distribution
+        .pots
+        .iter()
+        .map(|pot| (pot.amount, pot.eligible.clone()))
+        .collect();
+    assert_eq!(pots, vec![(150, vec![1, 2, 3]), (300, vec![2, 3])]);
+    assert_eq!(distribution.pots[0].shares, vec![(1, 150)]);
+    assert_eq!(distribution.pots[1].shares, vec![(2, 300)]);
+}
+
+#[test]
+fn test_multiple_side_pots() {
+    let players = [
+        player(
+            1,
+            30,
+            true,
+            Some(HandRank::FullHouse(Rank::Ace, Rank::King)),
+        ),
+        player(
+            2,
+            80,
+            true,
+            Some(HandRank::Flush(
+                Rank::Ace,
+                Rank::King,
+                Rank::Nine,
+                Rank::Four,
+                Rank::Two,
+            )),
+        ),
+        player(
+            3,
+            120,
+            false,
+            Some(HandRank::OnePair(
+                Rank::Two,
+                Rank::Five,
+                Rank::Four,
+                Rank::Three,
+            )),
+        ),
+        player(4, 120, false, None),
+    ];
+    let distribution = distribute(&players, 0).unwrap();
+    let amounts: Vec<u64> = distribution.pots.iter().map(|pot| pot.amount).collect();
+    assert_eq!(amounts, vec![120, 150, 80]);
+    assert_eq!(
+        distribution.payouts.into_iter().collect::<Vec<_>>(),
+        vec![(1, 120), (2, 150), (3, 80)]
+    );
+}
+
+#[test]
+fn test_split_pot_gives_odd_chips_left_of_the_button() {
+    let straight = Some(HandRank::Straight(Rank::Ten));
+    let players = [
+        player(1, 50, false, straight),
+        player(3, 50, false, straight),
+        player(5, 50, false, None),
+        player(6, 50, false, straight),
+    ];
+    let distribution = distribute(&players, 3).unwrap();
+    assert_eq!(distribution.pots.len(), 1);
+    assert_eq!(distribution.pots[0].amount, 200);
+    // Seat 6 is first to the left of the button on seat 3, so it gets the odd chip
+    assert_eq!(distribution.pots[0].shares, vec![(6, 67), (1, 67), (3, 66)]);
+}
+
+#[test]
+fn test_player_who_has_not_called_is_rejected() {
+    let players = [
+        player(1, 100, false, Some(HandRank::Straight(Rank::Nine))),
+        player(2, 60, false, Some(HandRank::Straight(Rank::Eight))),
+    ];
+    assert_eq!(distribute(&players, 0), Err(PotError::ShortContribution(2)));
+}
+
+#[test]
+fn test_pot_needs_a_live_player() {
+    let players = [player(1, 10, false, None), player(2, 10, false, None)];
+    assert_eq!(distribute(&players, 0), Err(PotError::NoLivePlayers));
+}
+
+const HISTORY: &str = "
+# Bob's sevens hold up
+Hand 1
+Seat 1: Alice
+Seat 2: Bob
+Blinds: Alice 5, Bob 10
+Dealt Alice: AS KD
+Dealt Bob: 7H 7C
+Preflop: Alice raises 30; Bob calls 25
+Flop 2C 7D KS: Bob checks; Alice bets 50; Bob calls 50
+Turn 3H: Bob checks; Alice checks
+River 9S: Bob bets 100; Alice calls 100
+Winner: Bob
+
+# Recorded with the wrong winner
+Hand 2
+Seat 1: Alice
+Seat 2: Bob
+Dealt Alice: AS AD
+Dealt Bob: 7H 2C
+Preflop: Alice calls 10; Bob checks
+Flop 2D 8D KS
+Turn 3H
+River 9S
+Winner: Bob
+
+Hand 3
+Seat 1: Alice
+Seat 2: Bob
+Dealt Alice: AS KD
+Preflop: Alice raises 30; Bob folds
+Winner: Alice
+
+Hand 4
+Seat 1: Alice
+Seat 2: Bob
+Dealt Alice: AS KD
+Dealt Bob: AS 2C
+Winner: Alice
+";
+
+#[test]
+fn test_parse_hand_history() {
+    let histories = parse_histories(HISTORY);
+    assert_eq!(histories.len(), 4);
+    let hand = histories[0].as_ref().unwrap();
+    assert_eq!(hand.id, "1");
+    assert_eq!(
+        hand.blinds,
+        vec![("Alice".to_string(), 5), ("Bob".to_string(), 10)]
+    );
+    assert_eq!(hand.board.len(), 5);
+    assert_eq!(hand.streets.len(), 4);
+    assert_eq!(hand.winners, vec!["Bob"]);
+}
+
+#[test]
+fn test_replay_reports_each_hand() {
+    let replays = replay_histories(HISTORY);
+    let first = replays[0].as_ref().unwrap();
+    assert!(first.is_match());
+    assert_eq!(
+        first.showdown[1].1,
+        HandRank::ThreeOfAKind(Rank::Seven, Rank::King, Rank::Nine)
+    );
+
+    let second = replays[1].as_ref().unwrap();
+    assert!(!second.is_match());
+    assert_eq!(second.recorded, vec!["Bob"]);
+    assert_eq!(second.computed, vec!["Alice"]);
+
+    // Won without a showdown
+    let third = replays[2].as_ref().unwrap();
+    assert!(third.is_match());
+    assert!(third.showdown.is_empty());
+
+    assert_eq!(
+        replays[3],
+        Err(HistoryError::Parse(ParseHistoryError {
+            line: 38,
+            kind: ParseHistoryErrorKind::DuplicateCard(Card {
+                rank: Rank::Ace,
+                suit: Suit::Spades
+            }),
+        }))
+    );
+}
+
+#[test]
+fn test_hand_history_rejects_invalid_cards_and_players() {
+    let bad_card = "Hand 1\nSeat 1: Alice\nDealt Alice: AS 1X\nWinner: Alice";
+    assert_eq!(
+        bad_card.parse::<HandHistory>().unwrap_err().kind,
+        ParseHistoryErrorKind::InvalidCard("1X".to_string())
+    );
+    let unknown = "Hand 1\nSeat 1: Alice\nPreflop: Carol folds\nWinner: Alice";
+    assert_eq!(
+        unknown.parse::<HandHistory>().unwrap_err(),
+        ParseHistoryError {
+            line: 3,
+            kind: ParseHistoryErrorKind::UnknownPlayer("Carol".to_string())
+        }
+    );
+    let short_flop = "Hand 1\nSeat 1: Alice\nPreflop:\nFlop 2C 3D: \nWinner: Alice";
+    assert_eq!(
+        short_flop.parse::<HandHistory>().unwrap_err().kind,
+        ParseHistoryErrorKind::BoardCards {
+            street: Street::Flop,
+            expected: 3,
+            found: 2
+        }
+    );
+}
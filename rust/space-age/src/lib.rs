@@ -1,4 +1,13 @@
-#[derive(Debug)]
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use time::Date;
+
+/// The seconds in an Earth year of 365.25 days.
+pub const EARTH_ORBITAL_SECS: f64 = 31557600.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Duration {
     seconds: u64,
 }
@@ -9,44 +18,279 @@ impl From<u64> for Duration {
     }
 }
 
-pub trait Planet {
-    const ORBITAL_YEARS: f64;
-    const EARTH_ORBITAL_SECS: f64 = 31557600.0;
-    fn years_during(d: &Duration) -> f64 {
-        (d.seconds as f64) / (Self::ORBITAL_YEARS * Self::EARTH_ORBITAL_SECS)
+impl Duration {
+    pub fn seconds(&self) -> u64 {
+        self.seconds
+    }
+}
+
+/// Anything which orbits the Sun, with its orbital period in Earth years.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Body {
+    name: Cow<'static, str>,
+    orbital_years: f64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum BodyError {
+    /// The orbital period is not a positive, finite number of years.
+    InvalidPeriod,
+    /// The name is empty or only whitespace.
+    BlankName,
+    UnknownBody(String),
+    /// A body with the same name, ignoring case, is already registered.
+    DuplicateName(String),
+}
+
+impl Body {
+    pub const MERCURY: Body = Body::known("Mercury", 0.2408467);
+    pub const VENUS: Body = Body::known("Venus", 0.6151972);
+    pub const EARTH: Body = Body::known("Earth", 1.0000000);
+    pub const MARS: Body = Body::known("Mars", 1.8808158);
+    pub const JUPITER: Body = Body::known("Jupiter", 11.862615);
+    pub const SATURN: Body = Body::known("Saturn", 29.447498);
+    pub const URANUS: Body = Body::known("Uranus", 84.016846);
+    pub const NEPTUNE: Body = Body::known("Neptune", 164.79132);
+    pub const PLUTO: Body = Body::known("Pluto", 247.94);
+    pub const CERES: Body = Body::known("Ceres", 4.60);
+    pub const ERIS: Body = Body::known("Eris", 559.07);
+
+    /// The eight planets, then the dwarf planets Pluto, Ceres and Eris.
+    pub const KNOWN: [Body; 11] = [
+        Body::MERCURY,
+        Body::VENUS,
+        Body::EARTH,
+        Body::MARS,
+        Body::JUPITER,
+        Body::SATURN,
+        Body::URANUS,
+        Body::NEPTUNE,
+        Body::PLUTO,
+        Body::CERES,
+        Body::ERIS,
+    ];
+
+    const fn known(name: &'static str, orbital_years: f64) -> Self {
+        Body {
+            name: Cow::Borrowed(name),
+            orbital_years,
+        }
+    }
+
+    /// A body called `name`, without any surrounding whitespace.
+    pub fn new(name: impl Into<Cow<'static, str>>, orbital_years: f64) -> Result<Self, BodyError> {
+        let mut name = name.into();
+        let trimmed = name.trim();
+        if trimmed.is_empty() {
+            return Err(BodyError::BlankName);
+        }
+        if trimmed.len() != name.len() {
+            name = Cow::Owned(trimmed.to_string());
+        }
+        if orbital_years.is_finite() && orbital_years > 0.0 {
+            Ok(Body {
+                name,
+                orbital_years,
+            })
+        } else {
+            Err(BodyError::InvalidPeriod)
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How many Earth years one orbit takes.
+    pub fn orbital_years(&self) -> f64 {
+        self.orbital_years
+    }
+
+    /// How many of this body's years last as long as `d`.
+    pub fn years_during(&self, d: &Duration) -> f64 {
+        (d.seconds as f64) / self.orbital_secs()
+    }
+
+    /// How long `years` of this body's years last, to the nearest second, or
+    /// `None` if `years` is negative, not finite, or too long for a `Duration`.
+    pub fn duration_of(&self, years: f64) -> Option<Duration> {
+        let seconds = (years * self.orbital_secs()).round();
+        if seconds >= 0.0 && seconds < u64::MAX as f64 {
+            Some(Duration::from(seconds as u64))
+        } else {
+            None
+        }
+    }
+
+    /// The first birthday on this body, counted from midnight on `born`, which
+    /// falls on or after `today`. A birthday falls on the Earth date during
+    /// which the whole number of orbits completes. Returns `None` if that date
+    /// is too far in the future to represent.
+    pub fn next_birthday(&self, born: Date, today: Date) -> Option<Birthday> {
+        let elapsed = (today.to_julian_day() - born.to_julian_day()) as f64 * SECS_PER_DAY;
+        // Start from the estimate, then step to the right birthday in case
+        // rounding put it one out either way
+        let mut age = ((elapsed / self.orbital_secs()).floor() as u64).max(1);
+        while age > 1 && self.birthday(born, age - 1)? >= today {
+            age -= 1;
+        }
+        loop {
+            let date = self.birthday(born, age)?;
+            if date >= today {
+                return Some(Birthday { age, date });
+            }
+            age += 1;
+        }
+    }
+
+    /// The Earth date of the birthday when this body has orbited `age` times.
+    fn birthday(&self, born: Date, age: u64) -> Option<Date> {
+        let days = (age as f64 * self.orbital_secs() / SECS_PER_DAY).floor();
+        // Saturates for huge ages, which are then out of range below
+        let days = i32::try_from(days as i64).ok()?;
+        Date::from_julian_day(born.to_julian_day().checked_add(days)?).ok()
+    }
+
+    fn orbital_secs(&self) -> f64 {
+        self.orbital_years * EARTH_ORBITAL_SECS
     }
 }
 
-pub struct Mercury;
-pub struct Venus;
-pub struct Earth;
-pub struct Mars;
-pub struct Jupiter;
-pub struct Saturn;
-pub struct Uranus;
-pub struct Neptune;
+const SECS_PER_DAY: f64 = 86_400.0;
 
-impl Planet for Mercury {
-    const ORBITAL_YEARS: f64 = 0.2408467;
+/// A whole-number birthday on a body, from `Body::next_birthday`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Birthday {
+    /// The number of the body's years old on this birthday.
+    pub age: u64,
+    /// The Earth date of the birthday.
+    pub date: Date,
 }
-impl Planet for Venus {
-    const ORBITAL_YEARS: f64 = 0.6151972;
+
+/// Looks up one of the `Body::KNOWN` bodies by name, ignoring case.
+impl FromStr for Body {
+    type Err = BodyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        find(&Body::KNOWN, s)
+            .cloned()
+            .ok_or_else(|| BodyError::UnknownBody(s.to_string()))
+    }
 }
-impl Planet for Earth {
-    const ORBITAL_YEARS: f64 = 1.0000000;
+
+fn find<'a>(bodies: &'a [Body], name: &str) -> Option<&'a Body> {
+    let name = name.trim();
+    bodies
+        .iter()
+        .find(|body| body.name.eq_ignore_ascii_case(name))
 }
-impl Planet for Mars {
-    const ORBITAL_YEARS: f64 = 1.8808158;
+
+/// The bodies which can be looked up by name: the `Body::KNOWN` bodies, and
+/// any registered since.
+#[derive(Debug, Clone)]
+pub struct Registry {
+    bodies: Vec<Body>,
 }
-impl Planet for Jupiter {
-    const ORBITAL_YEARS: f64 = 11.862615;
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry {
+            bodies: Body::KNOWN.to_vec(),
+        }
+    }
+
+    pub fn register(&mut self, body: Body) -> Result<(), BodyError> {
+        if find(&self.bodies, &body.name).is_some() {
+            return Err(BodyError::DuplicateName(body.name.into_owned()));
+        }
+        self.bodies.push(body);
+        Ok(())
+    }
+
+    /// The body called `name`, ignoring case.
+    pub fn get(&self, name: &str) -> Option<&Body> {
+        find(&self.bodies, name)
+    }
+
+    /// The body called `name`, as for `Body::from_str` but including the
+    /// registered bodies.
+    pub fn parse(&self, name: &str) -> Result<Body, BodyError> {
+        self.get(name)
+            .cloned()
+            .ok_or_else(|| BodyError::UnknownBody(name.to_string()))
+    }
+
+    /// The bodies in the order they were registered, after the known bodies.
+    pub fn iter(&self) -> impl Iterator<Item = &Body> {
+        self.bodies.iter()
+    }
+
+    /// The next birthday on each body, as for `Body::next_birthday`, in the
+    /// same order as `iter`. Bodies whose next birthday is too far in the
+    /// future to represent are left out.
+    pub fn next_birthdays(
+        &self,
+        born: Date,
+        today: Date,
+    ) -> impl Iterator<Item = (&Body, Birthday)> {
+        self.bodies
+            .iter()
+            .filter_map(move |body| Some((body, body.next_birthday(born, today)?)))
+    }
 }
-impl Planet for Saturn {
-    const ORBITAL_YEARS: f64 = 29.447498;
+
+impl Default for Registry {
+    fn default() -> Self {
+        Registry::new()
+    }
 }
-impl Planet for Uranus {
-    const ORBITAL_YEARS: f64 = 84.016846;
+
+pub trait Planet {
+    const ORBITAL_YEARS: f64;
+    const EARTH_ORBITAL_SECS: f64 = EARTH_ORBITAL_SECS;
+
+    /// The body this planet stands for. By default it is named after the type,
+    /// and orbits once every `ORBITAL_YEARS` years of `EARTH_ORBITAL_SECS`.
+    fn body() -> Body {
+        Body::known(
+            std::any::type_name::<Self>(),
+            Self::ORBITAL_YEARS * (Self::EARTH_ORBITAL_SECS / EARTH_ORBITAL_SECS),
+        )
+    }
+
+    fn years_during(d: &Duration) -> f64 {
+        Self::body().years_during(d)
+    }
+
+    fn duration_of(years: f64) -> Option<Duration> {
+        Self::body().duration_of(years)
+    }
 }
-impl Planet for Neptune {
-    const ORBITAL_YEARS: f64 = 164.79132;
+
+/// Implements `Planet` for a unit struct as a wrapper around a `Body` constant.
+macro_rules! planet {
+    ($planet:ident, $body:ident) => {
+        pub struct $planet;
+
+        impl Planet for $planet {
+            const ORBITAL_YEARS: f64 = {
+                // Read through a reference, as a `Body` cannot be dropped in a constant
+                const BODY: &Body = &Body::$body;
+                BODY.orbital_years
+            };
+
+            fn body() -> Body {
+                Body::$body
+            }
+        }
+    };
 }
+
+planet!(Mercury, MERCURY);
+planet!(Venus, VENUS);
+planet!(Earth, EARTH);
+planet!(Mars, MARS);
+planet!(Jupiter, JUPITER);
+planet!(Saturn, SATURN);
+planet!(Uranus, URANUS);
+planet!(Neptune, NEPTUNE);
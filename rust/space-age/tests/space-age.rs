@@ -1,4 +1,6 @@
 use space_age::*;
+use std::convert::TryFrom;
+use time::{Date, Month};
 
 fn assert_in_delta(expected: f64, actual: f64) {
     let diff: f64 = (expected - actual).abs();
@@ -11,6 +13,10 @@ fn assert_in_delta(expected: f64, actual: f64) {
     }
 }
 
+fn date(year: i32, month: u8, day: u8) -> Date {
+    Date::from_calendar_date(year, Month::try_from(month).unwrap(), day).unwrap()
+}
+
 #[test]
 fn earth_age() {
     let duration = Duration::from(1_000_000_000);
@@ -58,3 +64,192 @@ fn neptune_age() {
     let duration = Duration::from(8_210_123_456);
     assert_in_delta(1.58, Neptune::years_during(&duration));
 }
+
+#[test]
+fn body_from_name() {
+    let mars: Body = "mars".parse().unwrap();
+    assert_eq!(mars, Body::MARS);
+    assert_eq!(mars.name(), "Mars");
+    assert_eq!(" Eris ".parse(), Ok(Body::ERIS));
+    assert_eq!(
+        "Vulcan".parse::<Body>(),
+        Err(BodyError::UnknownBody("Vulcan".to_string()))
+    );
+}
+
+#[test]
+fn body_age_matches_planet() {
+    let duration = Duration::from(2_329_871_239);
+    let mars: Body = "Mars".parse().unwrap();
+    assert_eq!(mars.years_during(&duration), Mars::years_during(&duration));
+}
+
+#[test]
+fn planets_wrap_bodies() {
+    assert_eq!(<Mars as Planet>::ORBITAL_YEARS, Body::MARS.orbital_years());
+    assert_eq!(<Earth as Planet>::EARTH_ORBITAL_SECS, EARTH_ORBITAL_SECS);
+    assert_eq!(Neptune::body(), Body::NEPTUNE);
+}
+
+#[test]
+fn planet_implemented_outside_the_crate() {
+    struct Vulcan;
+    impl Planet for Vulcan {
+        const ORBITAL_YEARS: f64 = 2.0;
+    }
+    let duration = Duration::from(1_000_000_000);
+    assert_in_delta(15.84, Vulcan::years_during(&duration));
+    assert_eq!(Vulcan::body().orbital_years(), 2.0);
+}
+
+#[test]
+fn dwarf_planet_ages() {
+    let duration = Duration::from(1_000_000_000);
+    assert_in_delta(0.13, Body::PLUTO.years_during(&duration));
+    assert_in_delta(6.89, Body::CERES.years_during(&duration));
+    assert_in_delta(0.06, Body::ERIS.years_during(&duration));
+}
+
+#[test]
+fn known_bodies_in_order() {
+    let known = Body::KNOWN;
+    let names: Vec<&str> = known.iter().map(Body::name).collect();
+    assert_eq!(
+        names,
+        [
+            "Mercury", "Venus", "Earth", "Mars", "Jupiter", "Saturn", "Uranus", "Neptune", "Pluto",
+            "Ceres", "Eris"
+        ]
+    );
+}
+
+#[test]
+fn custom_body_period_must_be_positive() {
+    assert_eq!(Body::new("Nowhere", 0.0), Err(BodyError::InvalidPeriod));
+    assert_eq!(Body::new("Nowhere", -1.0), Err(BodyError::InvalidPeriod));
+    assert_eq!(
+        Body::new("Nowhere", f64::NAN),
+        Err(BodyError::InvalidPeriod)
+    );
+}
+
+#[test]
+fn custom_body_name_must_not_be_blank() {
+    assert_eq!(Body::new("", 1.0), Err(BodyError::BlankName));
+    assert_eq!(Body::new(" \t", 1.0), Err(BodyError::BlankName));
+    assert_eq!(Body::new(" Sedna ", 1.0).unwrap().name(), "Sedna");
+}
+
+#[test]
+fn register_custom_body() {
+    let mut registry = Registry::new();
+    let sedna = Body::new(String::from("Sedna"), 11_400.0).unwrap();
+    registry.register(sedna.clone()).unwrap();
+
+    assert_eq!(registry.get("sedna"), Some(&sedna));
+    assert_eq!(registry.parse("SEDNA"), Ok(sedna));
+    assert_eq!(registry.parse("Jupiter"), Ok(Body::JUPITER));
+    assert_eq!(registry.iter().count(), 12);
+    // Parsing without the registry only knows the built-in bodies
+    assert!("Sedna".parse::<Body>().is_err());
+}
+
+#[test]
+fn register_duplicate_body() {
+    let mut registry = Registry::new();
+    let fake_mars = Body::new("MARS", 2.0).unwrap();
+    assert_eq!(
+        registry.register(fake_mars),
+        Err(BodyError::DuplicateName("MARS".to_string()))
+    );
+    assert_eq!(registry.get("Mars"), Some(&Body::MARS));
+}
+
+#[test]
+fn duration_of_planet_years() {
+    assert_eq!(Earth::duration_of(1.0), Some(Duration::from(31_557_600)));
+    assert_eq!(
+        Body::MARS.duration_of(2.0),
+        Some(Duration::from(118_708_065))
+    );
+    assert_eq!(Body::EARTH.duration_of(0.0), Some(Duration::from(0)));
+}
+
+#[test]
+fn duration_of_round_trips() {
+    let duration = Duration::from(2_329_871_239);
+    let years = Body::NEPTUNE.years_during(&duration);
+    assert_eq!(Body::NEPTUNE.duration_of(years), Some(duration));
+}
+
+#[test]
+fn duration_of_rejects_negative_and_huge() {
+    assert_eq!(Mars::duration_of(-1.0), None);
+    assert_eq!(Mars::duration_of(f64::NAN), None);
+    assert_eq!(Mars::duration_of(1e20), None);
+}
+
+#[test]
+fn duration_of_planet_implemented_outside_the_crate() {
+    struct Vulcan;
+    impl Planet for Vulcan {
+        const ORBITAL_YEARS: f64 = 2.0;
+    }
+    assert_eq!(Vulcan::duration_of(0.5), Some(Duration::from(31_557_600)));
+}
+
+#[test]
+fn next_earth_birthday() {
+    let born = date(2000, 1, 1);
+    let today = date(2024, 6, 15);
+    let birthday = Body::EARTH.next_birthday(born, today).unwrap();
+    // Earth years are 365.25 days, and with seven leap days since 2000 the
+    // 25th completes at 06:00 on New Year's Eve
+    assert_eq!(birthday.age, 25);
+    assert_eq!(birthday.date, date(2024, 12, 31));
+    assert_eq!((birthday.date - today).whole_days(), 199);
+}
+
+#[test]
+fn next_mars_birthday() {
+    let born = date(2000, 1, 1);
+    let today = date(2024, 6, 15);
+    let birthday = Body::MARS.next_birthday(born, today).unwrap();
+    assert_eq!(birthday.age, 14);
+    assert_eq!(birthday.date, date(2026, 5, 1));
+}
+
+#[test]
+fn birthday_today_is_next() {
+    let born = date(2000, 1, 1);
+    let today = date(2026, 5, 1);
+    let birthday = Body::MARS.next_birthday(born, today).unwrap();
+    assert_eq!(birthday.age, 14);
+    assert_eq!(birthday.date, today);
+}
+
+#[test]
+fn first_birthday_before_birth() {
+    let born = date(2020, 3, 1);
+    let today = date(2019, 1, 1);
+    let birthday = Body::MERCURY.next_birthday(born, today).unwrap();
+    assert_eq!(birthday.age, 1);
+    assert_eq!(birthday.date, date(2020, 5, 27));
+}
+
+#[test]
+fn next_birthdays_on_each_body() {
+    let born = date(1990, 7, 4);
+    let today = date(2024, 1, 1);
+    let registry = Registry::new();
+    let birthdays: Vec<(&str, Birthday)> = registry
+        .next_birthdays(born, today)
+        .map(|(body, birthday)| (body.name(), birthday))
+        .collect();
+    assert_eq!(birthdays.len(), 11);
+    assert_eq!(birthdays[2].0, "Earth");
+    assert_eq!(birthdays[2].1.age, 34);
+    assert_eq!(birthdays[8].0, "Pluto");
+    assert_eq!(birthdays[8].1.age, 1);
+    assert!(birthdays.iter().all(|(_, birthday)| birthday.date >= today));
+}